@@ -0,0 +1,548 @@
+//! AtCoder のページ取得とスクレイピング
+
+use crate::cache::{Cache, Entry};
+use crate::config::{CacheConfig, Config};
+use crate::contest::{self, Contest, ContestSchedule, ContestStatus, ContestTasks};
+use crate::editorial::{self, EditorialArticle, EditorialIndex};
+use crate::http::HttpClient;
+use crate::problem::{self, ProblemPage};
+use crate::schema::SchemaType;
+use crate::time;
+use reqwest::{StatusCode, header};
+use scraper::{ElementRef, Selector};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::str::FromStr;
+
+/// 問題文の言語
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Lang {
+    Ja,
+    En,
+    Both,
+}
+
+impl FromStr for Lang {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "ja" => Ok(Lang::Ja),
+            "en" => Ok(Lang::En),
+            "both" => Ok(Lang::Both),
+            other => anyhow::bail!("Unknown language: {}", other),
+        }
+    }
+}
+
+impl SchemaType for Lang {
+    fn schema() -> Value {
+        json!({ "type": "string", "enum": ["ja", "en", "both"] })
+    }
+}
+
+/// #task-statement から指定された言語の部分だけを選ぶ
+///
+/// AtCoder の問題文は `span.lang-ja` と `span.lang-en` を両方含む。
+/// 英語版が無い (古い問題など) ときは日本語版に、言語の区切りが無いときは全体にフォールバックする。
+pub fn select_lang(statement: ElementRef<'_>, lang: Lang) -> ElementRef<'_> {
+    let class = match lang {
+        Lang::Ja => "span.lang-ja",
+        Lang::En => "span.lang-en",
+        Lang::Both => return statement,
+    };
+    let find = |selector: &str| {
+        let selector = Selector::parse(selector).unwrap();
+        statement
+            .select(&selector)
+            .find(|e| !e.text().collect::<String>().trim().is_empty())
+    };
+    find(class)
+        .or_else(|| find("span.lang-ja"))
+        .unwrap_or(statement)
+}
+
+/// ページごとのキャッシュの持たせ方
+#[derive(Debug, Clone, Copy)]
+pub enum CachePolicy {
+    /// コンテストのページ: 終わったコンテストなら期限なし、開催中は短め
+    ContestPage,
+    /// 解説一覧のように後から増えていくページ
+    Growing,
+}
+
+/// atcoder.jp へのアクセスをまとめたもの (サーバー全体で1つを共有する)
+pub struct AtCoder {
+    http: HttpClient,
+    cache: Cache,
+    cache_config: CacheConfig,
+}
+
+impl AtCoder {
+    pub fn new(config: &Config) -> anyhow::Result<Self> {
+        Ok(Self {
+            http: HttpClient::new(&config.http)?,
+            cache: Cache::new(&config.cache),
+            cache_config: config.cache.clone(),
+        })
+    }
+
+    pub fn cache(&self) -> &Cache {
+        &self.cache
+    }
+
+    /// ページを取得する (キャッシュがあればそれを使い、期限切れなら再検証する)
+    pub async fn fetch_page(&self, url: &str, policy: CachePolicy) -> anyhow::Result<String> {
+        let now = time::now();
+        let cached = self.cache.get(url).await;
+        if let Some(entry) = &cached
+            && entry.is_fresh(now)
+        {
+            return Ok(entry.body.clone());
+        }
+
+        let mut request = self.http.get(url);
+        if let Some(entry) = &cached {
+            if let Some(etag) = &entry.etag {
+                request = request.header(header::IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &entry.last_modified {
+                request = request.header(header::IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+
+        let resp = match self.http.send(request).await {
+            Ok(resp) => resp,
+            // 通信できなくても、古いキャッシュがあればそれで済ませる
+            Err(e) => return cached.map(|entry| entry.body).ok_or(e),
+        };
+
+        let status = resp.status();
+        if let Some(mut entry) = cached {
+            if status == StatusCode::NOT_MODIFIED {
+                entry.fetched_at = now;
+                entry.expires_at = self.expires_at(policy, &entry.body, now);
+                self.cache.put(&entry).await;
+                return Ok(entry.body);
+            }
+            if status.is_server_error() {
+                return Ok(entry.body);
+            }
+        }
+        if !status.is_success() {
+            anyhow::bail!("Failed to fetch {}. Status: {}", url, status);
+        }
+
+        let header_value = |name| {
+            resp.headers()
+                .get(name)
+                .and_then(|v: &header::HeaderValue| v.to_str().ok())
+                .map(str::to_string)
+        };
+        let etag = header_value(header::ETAG);
+        let last_modified = header_value(header::LAST_MODIFIED);
+        let body = resp.text().await?;
+
+        let entry = Entry {
+            url: url.to_string(),
+            fetched_at: now,
+            expires_at: self.expires_at(policy, &body, now),
+            etag,
+            last_modified,
+            body,
+        };
+        self.cache.put(&entry).await;
+        Ok(entry.body)
+    }
+
+    /// キャッシュの期限
+    fn expires_at(&self, policy: CachePolicy, body: &str, now: u64) -> Option<u64> {
+        match policy {
+            CachePolicy::ContestPage
+                if contest::embedded_time(body, "endTime").is_some_and(|end| end <= now as i64) =>
+            {
+                None
+            }
+            CachePolicy::ContestPage => Some(now + self.cache_config.running_contest_ttl_secs),
+            CachePolicy::Growing => Some(now + self.cache_config.editorial_ttl_secs),
+        }
+    }
+
+    /// スクレイピング機能: 指定した問題のHTMLを取得して解析する
+    pub async fn fetch_problem(
+        &self,
+        contest_id: &str,
+        problem_id: &str,
+        lang: Lang,
+    ) -> anyhow::Result<ProblemPage> {
+        let problem_id = self.resolve_problem_id(contest_id, problem_id).await?;
+        let url = format!(
+            "https://atcoder.jp/contests/{}/tasks/{}",
+            contest_id, problem_id
+        );
+        let body = self.fetch_page(&url, CachePolicy::ContestPage).await?;
+        problem::parse_problem_page(&body, contest_id, &problem_id, &url, lang)
+    }
+
+    /// コンテストの問題一覧を取得
+    pub async fn fetch_tasks(&self, contest_id: &str) -> anyhow::Result<ContestTasks> {
+        let url = format!("https://atcoder.jp/contests/{}/tasks", contest_id);
+        let body = self.fetch_page(&url, CachePolicy::ContestPage).await?;
+        contest::parse_tasks_page(&body, contest_id, &url)
+    }
+
+    /// コンテストのトップページから、開催時間や Rated 対象を取得
+    pub async fn fetch_contest(&self, contest_id: &str) -> anyhow::Result<Contest> {
+        let url = format!("https://atcoder.jp/contests/{}", contest_id);
+        let body = self.fetch_page(&url, CachePolicy::ContestPage).await?;
+        contest::parse_contest_page(&body, contest_id, time::now() as i64)
+    }
+
+    /// 解説やヒントを出してよいコンテストか (終了済みか常設) を確かめる
+    ///
+    /// 開催中の問題のネタバレを防ぐためなので、コンテストページが読めないときや
+    /// 開催時刻が分からないときも出さない側に倒す。
+    pub async fn ensure_contest_finished(&self, contest_id: &str) -> anyhow::Result<()> {
+        let contest = self.fetch_contest(contest_id).await.map_err(|e| {
+            anyhow::anyhow!(
+                "Could not check whether {} has ended ({}), so its editorial and hints are withheld.",
+                contest_id,
+                e
+            )
+        })?;
+        match contest.status {
+            ContestStatus::Archived | ContestStatus::Permanent => Ok(()),
+            ContestStatus::Running => anyhow::bail!(
+                "{} is running until {}. Editorials and hints are disabled until the contest ends.",
+                contest.name,
+                contest
+                    .end_time
+                    .as_deref()
+                    .unwrap_or("the end of the contest")
+            ),
+            ContestStatus::Upcoming => anyhow::bail!(
+                "{} has not started yet. Editorials and hints are disabled until the contest ends.",
+                contest.name
+            ),
+            ContestStatus::Unknown => anyhow::bail!(
+                "Could not determine whether {} has ended, so its editorial and hints are withheld.",
+                contest.name
+            ),
+        }
+    }
+
+    /// コンテスト一覧 (開催中・開始前・最近終わった・常設) を取得
+    pub async fn fetch_contests(&self) -> anyhow::Result<ContestSchedule> {
+        // 終了時刻を持たないページなので、開催中のコンテストと同じ短い期限でキャッシュされる
+        let body = self
+            .fetch_page("https://atcoder.jp/contests/", CachePolicy::ContestPage)
+            .await?;
+        contest::parse_contests_page(&body, time::now() as i64)
+    }
+
+    /// 問題を問題一覧と照らし合わせて、本当の問題IDにする
+    ///
+    /// 記号 (例: A) は問題一覧から問題IDを引く (abc042 の A が arc058_a のように推測できないことがある)。
+    /// 問題IDはそのコンテストにあるかを確かめる。ただし一覧が読めないとき (開催前など) はそのまま使う
+    pub async fn resolve_problem_id(
+        &self,
+        contest_id: &str,
+        problem_id: &str,
+    ) -> anyhow::Result<String> {
+        let is_letter = contest::looks_like_letter(problem_id);
+        let tasks = match self.fetch_tasks(contest_id).await {
+            Ok(tasks) => tasks,
+            Err(_) if !is_letter => return Ok(problem_id.to_string()),
+            Err(e) => return Err(e),
+        };
+        // 記号は A 〜 Ex のほか、tessoku-book の A01 のような形もあるので、問題IDで見つからなければ記号として探す
+        let found = tasks
+            .tasks
+            .iter()
+            .find(|t| t.task_id.eq_ignore_ascii_case(problem_id.trim()))
+            .or_else(|| tasks.find_by_letter(problem_id));
+        match found {
+            Some(task) => Ok(task.task_id.clone()),
+            None => anyhow::bail!(
+                "{} has no task {}. Tasks: {}",
+                contest_id,
+                problem_id,
+                tasks
+                    .tasks
+                    .iter()
+                    .map(|t| format!("{} ({})", t.letter, t.task_id))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+
+    /// 解説ページ(一覧)を取得して、公式解説とユーザー解説に分ける
+    pub async fn fetch_editorial(
+        &self,
+        contest_id: &str,
+        problem_id: &str,
+    ) -> anyhow::Result<EditorialIndex> {
+        let problem_id = self.resolve_problem_id(contest_id, problem_id).await?;
+        let url = format!(
+            "https://atcoder.jp/contests/{}/tasks/{}/editorial",
+            contest_id, problem_id
+        );
+        let body = self.fetch_page(&url, CachePolicy::Growing).await?;
+        editorial::parse_editorial_index(&body, contest_id, &problem_id, &url)
+    }
+
+    /// 個別の解説ページを取得して本文を Markdown にする
+    pub async fn fetch_editorial_article(&self, url: &str) -> anyhow::Result<EditorialArticle> {
+        let url = editorial::article_url(url)?;
+        // 解説は書き直されることがあるので、一覧と同じく期限付きでキャッシュする
+        let body = self.fetch_page(&url, CachePolicy::Growing).await?;
+        editorial::parse_editorial_article(&body, &url)
+    }
+
+    /// 公式解説の本文 (プロンプトに埋め込む用)。公式解説が無ければ一覧を返す
+    pub async fn fetch_official_editorial(
+        &self,
+        contest_id: &str,
+        problem_id: &str,
+        lang: Lang,
+    ) -> anyhow::Result<String> {
+        let index = self.fetch_editorial(contest_id, problem_id).await?;
+        let Some(entry) = index.official(lang) else {
+            return Ok(index.to_markdown());
+        };
+        let article = self.fetch_editorial_article(&entry.url).await?;
+        Ok(article.to_markdown())
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::sync::{Arc, Mutex};
+
+    /// 終わったコンテスト (abc335) とその C 問題の問題文・解説、開始前のコンテスト (arc999)
+    pub(crate) const PAGES: &[(&str, &str)] = &[
+        (
+            "https://atcoder.jp/contests/abc335",
+            r#"<div id="main-container"><h1 class="contest-title">ABC 335</h1></div>
+            <script>var startTime = moment("2024-01-06T21:00:00+09:00");
+            var endTime = moment("2024-01-06T22:40:00+09:00");</script>"#,
+        ),
+        (
+            "https://atcoder.jp/contests/abc335/tasks",
+            r#"<div id="main-container"><table><tbody>
+            <tr><td><a href="/contests/abc335/tasks/abc335_c">C</a></td><td>Loong Tracking</td><td>2 sec</td><td>1024 MB</td></tr>
+            </tbody></table></div>"#,
+        ),
+        (
+            "https://atcoder.jp/contests/abc335/tasks/abc335_c/editorial",
+            r#"<div id="main-container"><h3>公式解説</h3><ul>
+            <li><a href="/contests/abc335/editorial/9041">解説</a> by <a class="username" href="/users/evima">evima</a></li>
+            </ul></div>"#,
+        ),
+        (
+            "https://atcoder.jp/contests/abc335/editorial/9041",
+            r#"<div id="main-container"><h2>C - Loong Tracking 解説</h2>
+            <div id="editorial"><p>頭の位置の履歴を持ちます。</p></div></div>"#,
+        ),
+        (
+            "https://atcoder.jp/contests/arc999",
+            r#"<div id="main-container"><h1 class="contest-title">ARC 999</h1></div>
+            <script>var startTime = moment("2999-01-06T21:00:00+09:00");
+            var endTime = moment("2999-01-06T23:00:00+09:00");</script>"#,
+        ),
+        (
+            "https://atcoder.jp/contests/abc335/tasks/abc335_c",
+            r#"<span class="h2">C - Loong Tracking</span>
+            <div id="task-statement"><span class="lang"><span class="lang-ja">
+            <section><h3>問題文</h3><p>竜を動かします。</p></section>
+            </span></span></div>"#,
+        ),
+    ];
+
+    /// 今開催中のコンテストのページ
+    pub(crate) fn running_contest_page(name: &str) -> String {
+        let now = time::now() as i64;
+        format!(
+            r#"<div id="main-container"><h1 class="contest-title">{}</h1></div>
+            <script>var startTime = moment("{}"); var endTime = moment("{}");</script>"#,
+            name,
+            time::format_datetime(now - 3600, 9 * 3600),
+            time::format_datetime(now + 3600, 9 * 3600)
+        )
+    }
+
+    /// `pages` をキャッシュに入れた AtCoder (入れたページはアクセスせずに返す)
+    pub(crate) async fn offline(name: &str, pages: &[(&str, &str)]) -> AtCoder {
+        let atcoder = atcoder(name);
+        for (url, body) in pages {
+            let entry = Entry {
+                url: url.to_string(),
+                fetched_at: 0,
+                expires_at: None,
+                etag: None,
+                last_modified: None,
+                body: body.to_string(),
+            };
+            atcoder.cache().put(&entry).await;
+        }
+        atcoder
+    }
+
+    fn atcoder(name: &str) -> AtCoder {
+        let dir = std::env::temp_dir().join(format!(
+            "atcoder-mcp-atcoder-test-{}-{}",
+            std::process::id(),
+            name
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        let mut config = Config::default();
+        config.cache.dir = Some(dir);
+        config.http.requests_per_second = 0.0;
+        config.http.max_retries = 0;
+        AtCoder::new(&config).unwrap()
+    }
+
+    /// リクエストのヘッダーを記録し、If-None-Match が付いていれば 304 を返すサーバー
+    fn revalidating_server() -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!(
+            "http://{}/contests/abc335/editorial",
+            listener.local_addr().unwrap()
+        );
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let seen = requests.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut head = String::new();
+                let mut reader = BufReader::new(&stream);
+                while reader.read_line(&mut head).unwrap() > 2 && !head.ends_with("\r\n\r\n") {}
+                let head = head.to_ascii_lowercase();
+                let response = if head.contains("if-none-match: \"v1\"") {
+                    "HTTP/1.1 304 Not Modified\r\nConnection: close\r\n\r\n".to_string()
+                } else {
+                    "HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nLast-Modified: Sat, 06 Jan 2024 13:40:00 GMT\r\nContent-Length: 5\r\nConnection: close\r\n\r\nfirst".to_string()
+                };
+                seen.lock().unwrap().push(head);
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        (url, requests)
+    }
+
+    #[test]
+    fn expiry_depends_on_contest_end() {
+        let atcoder = atcoder("expiry");
+        let now = time::parse_datetime("2024-01-07T00:00:00+09:00").unwrap() as u64;
+        let page = |end: &str| format!("<script>var endTime = moment(\"{}\");</script>", end);
+        let ended = page("2024-01-06T22:40:00+09:00");
+        let running = page("2024-01-07T01:00:00+09:00");
+
+        assert_eq!(
+            atcoder.expires_at(CachePolicy::ContestPage, &ended, now),
+            None
+        );
+        assert_eq!(
+            atcoder.expires_at(CachePolicy::ContestPage, &running, now),
+            Some(now + 5 * 60)
+        );
+        // 終了時刻が分からないページは開催中と同じ扱い
+        assert_eq!(
+            atcoder.expires_at(CachePolicy::ContestPage, "<html>", now),
+            Some(now + 5 * 60)
+        );
+        // 解説一覧は終わったコンテストでも期限付き
+        assert_eq!(
+            atcoder.expires_at(CachePolicy::Growing, &ended, now),
+            Some(now + 60 * 60)
+        );
+    }
+
+    #[tokio::test]
+    async fn revalidates_expired_entry_with_etag_and_last_modified() {
+        let atcoder = atcoder("revalidate");
+        let (url, requests) = revalidating_server();
+
+        assert_eq!(
+            atcoder
+                .fetch_page(&url, CachePolicy::Growing)
+                .await
+                .unwrap(),
+            "first"
+        );
+        // 期限内ならアクセスしない
+        assert_eq!(
+            atcoder
+                .fetch_page(&url, CachePolicy::Growing)
+                .await
+                .unwrap(),
+            "first"
+        );
+        assert_eq!(requests.lock().unwrap().len(), 1);
+
+        let mut entry = atcoder.cache().get(&url).await.unwrap();
+        assert_eq!(entry.etag.as_deref(), Some("\"v1\""));
+        entry.expires_at = Some(0);
+        atcoder.cache().put(&entry).await;
+
+        assert_eq!(
+            atcoder
+                .fetch_page(&url, CachePolicy::Growing)
+                .await
+                .unwrap(),
+            "first"
+        );
+        let requests = requests.lock().unwrap().clone();
+        assert_eq!(requests.len(), 2);
+        assert!(!requests[0].contains("if-none-match"));
+        assert!(requests[1].contains("if-modified-since: sat, 06 jan 2024 13:40:00 gmt"));
+        // 304 を受けたら期限を延ばす
+        let entry = atcoder.cache().get(&url).await.unwrap();
+        assert!(entry.is_fresh(time::now()));
+
+        let _ = std::fs::remove_dir_all(atcoder.cache().dir().unwrap());
+    }
+
+    #[tokio::test]
+    async fn withholds_editorial_until_contest_ends() {
+        let running = running_contest_page("ABC 998");
+        let atcoder = offline(
+            "finished",
+            &[
+                PAGES[0],
+                PAGES[4],
+                ("https://atcoder.jp/contests/abc998", &running),
+                (
+                    "https://atcoder.jp/contests/abc999",
+                    r#"<div id="main-container"><h1 class="contest-title">ABC 999</h1></div>"#,
+                ),
+            ],
+        )
+        .await;
+        atcoder.ensure_contest_finished("abc335").await.unwrap();
+        let error = atcoder.ensure_contest_finished("abc998").await.unwrap_err();
+        assert!(
+            error.to_string().starts_with("ABC 998 is running until"),
+            "{}",
+            error
+        );
+        let error = atcoder.ensure_contest_finished("arc999").await.unwrap_err();
+        assert!(
+            error.to_string().starts_with("ARC 999 has not started yet"),
+            "{}",
+            error
+        );
+        let error = atcoder.ensure_contest_finished("abc999").await.unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .starts_with("Could not determine whether ABC 999 has ended")
+        );
+
+        let _ = std::fs::remove_dir_all(atcoder.cache().dir().unwrap());
+    }
+}
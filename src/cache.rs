@@ -0,0 +1,293 @@
+//! 取得したページのディスクキャッシュ
+//!
+//! URL ごとに `$XDG_CACHE_HOME/atcoder-mcp/` (なければ `~/.cache/atcoder-mcp/`) へ JSON で保存する。
+//! 終わったコンテストの問題文は変わらないので期限なし、開催中のコンテストや
+//! 後から増えていく解説一覧は短い期限にして、期限切れのものは ETag / Last-Modified で再検証する。
+
+use crate::config::CacheConfig;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// 一時ファイル名の通し番号 (同じ URL を同時に書いても一時ファイルが重ならないように)
+static TMP_SEQ: AtomicU64 = AtomicU64::new(0);
+
+/// キャッシュ1件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    pub url: String,
+    /// 取得 (または再検証) した時刻 (UNIX 秒)
+    pub fetched_at: u64,
+    /// この時刻を過ぎたら再検証する (None なら期限なし)
+    pub expires_at: Option<u64>,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub body: String,
+}
+
+impl Entry {
+    pub fn is_fresh(&self, now: u64) -> bool {
+        self.expires_at.is_none_or(|expires| now < expires)
+    }
+}
+
+/// clear_cache の結果
+pub struct Cleared {
+    pub entries: u64,
+    pub bytes: u64,
+}
+
+/// ディスクキャッシュ (保存先が決められないときは何もしない)
+pub struct Cache {
+    dir: Option<PathBuf>,
+}
+
+impl Cache {
+    pub fn new(config: &CacheConfig) -> Self {
+        let dir = if config.enabled {
+            config.dir.clone().or_else(default_dir)
+        } else {
+            None
+        };
+        Self { dir }
+    }
+
+    /// 保存先のディレクトリ
+    pub fn dir(&self) -> Option<&std::path::Path> {
+        self.dir.as_deref()
+    }
+
+    /// URL に対応するキャッシュを読む (無い・壊れているときは None)
+    pub async fn get(&self, url: &str) -> Option<Entry> {
+        let path = self.path_for(url)?;
+        let text = tokio::fs::read_to_string(&path).await.ok()?;
+        serde_json::from_str::<Entry>(&text)
+            .ok()
+            .filter(|entry| entry.url == url)
+    }
+
+    /// キャッシュに書く。書けなくても取得自体は成功しているので、エラーにはしない
+    pub async fn put(&self, entry: &Entry) {
+        let Some(path) = self.path_for(&entry.url) else {
+            return;
+        };
+        let Ok(text) = serde_json::to_string(entry) else {
+            return;
+        };
+        // 途中まで書いたファイルを読まないように、一時ファイルに書いてから置き換える
+        let tmp = path.with_extension(format!(
+            "tmp{}-{}",
+            std::process::id(),
+            TMP_SEQ.fetch_add(1, Ordering::Relaxed)
+        ));
+        let write = async {
+            if let Some(dir) = path.parent() {
+                tokio::fs::create_dir_all(dir).await?;
+            }
+            tokio::fs::write(&tmp, text).await?;
+            tokio::fs::rename(&tmp, &path).await
+        };
+        if write.await.is_err() {
+            let _ = tokio::fs::remove_file(&tmp).await;
+        }
+    }
+
+    /// キャッシュを消す。`contest_id` を指定したらそのコンテストのページだけ
+    ///
+    /// 書き込みの途中で落ちて残った一時ファイルは、`contest_id` に関係なく消す
+    pub async fn clear(&self, contest_id: Option<&str>) -> anyhow::Result<Cleared> {
+        let mut cleared = Cleared {
+            entries: 0,
+            bytes: 0,
+        };
+        let Some(dir) = &self.dir else {
+            return Ok(cleared);
+        };
+        let mut entries = match tokio::fs::read_dir(dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(cleared),
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to read {}", dir.display()));
+            }
+        };
+
+        let needle = contest_id.map(|id| format!("/contests/{}/", id));
+        while let Some(file) = entries.next_entry().await? {
+            let path = file.path();
+            let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+            if extension.starts_with("tmp") {
+                let size = file.metadata().await.map(|m| m.len()).unwrap_or(0);
+                // 書き込み中のものなら put 側が rename に失敗して諦めるだけ
+                if tokio::fs::remove_file(&path).await.is_ok() {
+                    cleared.bytes += size;
+                }
+                continue;
+            }
+            if extension != "json" {
+                continue;
+            }
+            if let Some(needle) = &needle {
+                let url = tokio::fs::read_to_string(&path)
+                    .await
+                    .ok()
+                    .and_then(|text| serde_json::from_str::<Entry>(&text).ok())
+                    .map(|entry| entry.url);
+                // URL の末尾にスラッシュが無いコンテストのトップページも対象にする
+                if !url.is_some_and(|url| format!("{}/", url).contains(needle.as_str())) {
+                    continue;
+                }
+            }
+            let size = file.metadata().await.map(|m| m.len()).unwrap_or(0);
+            tokio::fs::remove_file(&path)
+                .await
+                .with_context(|| format!("Failed to remove {}", path.display()))?;
+            cleared.entries += 1;
+            cleared.bytes += size;
+        }
+        Ok(cleared)
+    }
+
+    fn path_for(&self, url: &str) -> Option<PathBuf> {
+        Some(self.dir.as_ref()?.join(format!("{:016x}.json", fnv1a(url))))
+    }
+}
+
+/// `$XDG_CACHE_HOME/atcoder-mcp` (なければ `~/.cache/atcoder-mcp`)
+fn default_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+    Some(base.join("atcoder-mcp"))
+}
+
+/// ファイル名用のハッシュ (std の Hasher はバージョン間で値が変わりうるので自前で持つ)
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// テストごとの空のキャッシュ
+    fn cache(name: &str) -> Cache {
+        let dir = std::env::temp_dir().join(format!(
+            "atcoder-mcp-cache-test-{}-{}",
+            std::process::id(),
+            name
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        Cache::new(&CacheConfig {
+            dir: Some(dir),
+            ..CacheConfig::default()
+        })
+    }
+
+    fn entry(url: &str, body: &str) -> Entry {
+        Entry {
+            url: url.to_string(),
+            fetched_at: 100,
+            expires_at: None,
+            etag: None,
+            last_modified: None,
+            body: body.to_string(),
+        }
+    }
+
+    fn files(cache: &Cache) -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(cache.dir().unwrap())
+            .unwrap()
+            .map(|file| file.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn entry_without_expiry_is_always_fresh() {
+        let mut entry = entry("https://atcoder.jp/contests/abc335", "");
+        assert!(entry.is_fresh(u64::MAX));
+        entry.expires_at = Some(200);
+        assert!(entry.is_fresh(199));
+        assert!(!entry.is_fresh(200));
+    }
+
+    #[tokio::test]
+    async fn put_then_get_round_trips() {
+        let cache = cache("round-trip");
+        let url = "https://atcoder.jp/contests/abc335/tasks/abc335_c";
+        assert!(cache.get(url).await.is_none());
+        cache.put(&entry(url, "<html>")).await;
+        assert_eq!(cache.get(url).await.unwrap().body, "<html>");
+        assert!(
+            cache
+                .get("https://atcoder.jp/contests/abc336")
+                .await
+                .is_none()
+        );
+        let _ = std::fs::remove_dir_all(cache.dir().unwrap());
+    }
+
+    #[tokio::test]
+    async fn concurrent_puts_do_not_share_tmp_file() {
+        let cache = std::sync::Arc::new(cache("concurrent"));
+        let url = "https://atcoder.jp/contests/abc335";
+        let bodies: Vec<String> = (0..16).map(|i| "x".repeat(100_000 + i)).collect();
+        let mut tasks = tokio::task::JoinSet::new();
+        for body in &bodies {
+            let (cache, entry) = (cache.clone(), entry(url, body));
+            tasks.spawn(async move { cache.put(&entry).await });
+        }
+        while tasks.join_next().await.is_some() {}
+        // どれか1つが丸ごと残り、一時ファイルは残らない
+        let body = cache.get(url).await.unwrap().body;
+        assert!(bodies.contains(&body));
+        assert_eq!(files(&cache).len(), 1);
+        let _ = std::fs::remove_dir_all(cache.dir().unwrap());
+    }
+
+    #[tokio::test]
+    async fn clear_filters_by_contest_and_removes_orphaned_tmp_files() {
+        let cache = cache("clear");
+        cache
+            .put(&entry("https://atcoder.jp/contests/abc335", "top"))
+            .await;
+        cache
+            .put(&entry(
+                "https://atcoder.jp/contests/abc335/tasks/abc335_c",
+                "task",
+            ))
+            .await;
+        cache
+            .put(&entry("https://atcoder.jp/contests/abc3350", "other"))
+            .await;
+        let dir = cache.dir().unwrap().to_path_buf();
+        std::fs::write(dir.join("0123456789abcdef.tmp42-7"), "partial").unwrap();
+
+        let cleared = cache.clear(Some("abc335")).await.unwrap();
+        assert_eq!(cleared.entries, 2);
+        assert!(files(&cache).iter().all(|name| name.ends_with(".json")));
+        assert!(
+            cache
+                .get("https://atcoder.jp/contests/abc3350")
+                .await
+                .is_some()
+        );
+
+        let cleared = cache.clear(None).await.unwrap();
+        assert_eq!(cleared.entries, 1);
+        assert!(files(&cache).is_empty());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn clear_without_directory_is_empty() {
+        let cache = cache("missing");
+        let cleared = cache.clear(None).await.unwrap();
+        assert_eq!((cleared.entries, cleared.bytes), (0, 0));
+    }
+}
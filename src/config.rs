@@ -0,0 +1,246 @@
+//! サーバー全体の設定
+//!
+//! `$XDG_CONFIG_HOME/atcoder-mcp/config.json` (なければ `~/.config/...`) を読む。
+//! 場所は環境変数 `ATCODER_MCP_CONFIG` で変えられる。ファイルが無ければ既定値で動く。
+//! エディタの MCP 設定から渡しやすいように、一部の項目は環境変数でも上書きできる。
+
+use crate::atcoder::Lang;
+use anyhow::Context;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// lang を指定しなかったときの問題文の言語 (環境変数 `ATCODER_MCP_LANG`)
+    pub default_lang: Lang,
+    /// run_samples などで使う言語ごとのコンパイル・実行コマンド
+    /// 設定ファイルに書いたものは既定の言語に追加 (同名なら上書き) される
+    pub languages: BTreeMap<String, LanguageConfig>,
+    /// 解答を実行するときの制限
+    pub sandbox: SandboxConfig,
+    /// 取得したページのキャッシュ
+    pub cache: CacheConfig,
+    /// atcoder.jp へのアクセスの間隔・再試行・タイムアウト
+    pub http: HttpConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            default_lang: Lang::Both,
+            languages: default_languages(),
+            sandbox: SandboxConfig::default(),
+            cache: CacheConfig::default(),
+            http: HttpConfig::default(),
+        }
+    }
+}
+
+/// HTTP アクセスの設定
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct HttpConfig {
+    /// ホストごとの1秒あたりのリクエスト数 (0 なら制限しない)
+    pub requests_per_second: f64,
+    /// 間隔を空けずに続けて送ってよい数
+    pub burst: u32,
+    /// 接続のタイムアウト (秒)
+    pub connect_timeout_secs: u64,
+    /// リクエスト全体のタイムアウト (秒)
+    pub timeout_secs: u64,
+    /// 5xx / 429 / タイムアウトのときに再試行する回数
+    pub max_retries: u32,
+    /// 最初の再試行までの待ち時間 (ミリ秒)。以降は倍々に延ばす
+    pub initial_backoff_ms: u64,
+    /// 再試行までの待ち時間の上限 (ミリ秒)。Retry-After がこれより長いときは再試行せずにエラーにする
+    pub max_backoff_ms: u64,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            requests_per_second: 1.0,
+            burst: 1,
+            connect_timeout_secs: 10,
+            timeout_secs: 30,
+            max_retries: 3,
+            initial_backoff_ms: 1000,
+            max_backoff_ms: 30_000,
+        }
+    }
+}
+
+/// ページキャッシュの設定
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct CacheConfig {
+    /// false ならキャッシュを使わない
+    pub enabled: bool,
+    /// 保存先 (省略時は `$XDG_CACHE_HOME/atcoder-mcp`)
+    pub dir: Option<PathBuf>,
+    /// 開催中 (か終了時刻が分からない) コンテストのページを再検証するまでの秒数
+    pub running_contest_ttl_secs: u64,
+    /// 解説一覧のように後から増えていくページを再検証するまでの秒数
+    pub editorial_ttl_secs: u64,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            dir: None,
+            running_contest_ttl_secs: 5 * 60,
+            editorial_ttl_secs: 60 * 60,
+        }
+    }
+}
+
+/// 解答の実行に掛ける制限の既定値
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SandboxConfig {
+    /// 問題ページから実行時間制限が取れなかったときの値 (ミリ秒)
+    pub default_time_limit_ms: u64,
+    /// 問題ページからメモリ制限が取れなかったときの値 (MB)
+    pub default_memory_limit_mb: u64,
+    /// 実時間の上限 = 実行時間制限 × この倍率 + wall_time_margin_ms
+    pub wall_time_factor: f64,
+    pub wall_time_margin_ms: u64,
+    /// 標準出力の上限 (バイト)
+    pub max_output_bytes: u64,
+    /// 解答が新しく作ってよいプロセス・スレッドの数 (null なら制限しない)
+    ///
+    /// RLIMIT_NPROC で掛けるが、この制限はユーザー単位なので、実行直前に数えた
+    /// 同じユーザーのプロセス数に上乗せした値になる。数えた後に他のプロセスが増減すると
+    /// ずれるので目安と考えること。root で動かしているときは RLIMIT_NPROC が効かないため掛けず、
+    /// 時間切れのウォッチドッグがプロセスグループごと止めるのに任せる。
+    pub max_processes: Option<u64>,
+}
+
+impl Default for SandboxConfig {
+    fn default() -> Self {
+        Self {
+            default_time_limit_ms: 2000,
+            default_memory_limit_mb: 1024,
+            wall_time_factor: 2.0,
+            wall_time_margin_ms: 1000,
+            max_output_bytes: 64 * 1024 * 1024,
+            max_processes: Some(64),
+        }
+    }
+}
+
+/// 1言語分のコンパイル・実行コマンド
+///
+/// コマンドはシェルを通さずに argv として実行する。次のプレースホルダが使える:
+/// `{src}` ソースファイル、`{exe}` 実行ファイルの出力先、`{dir}` 作業ディレクトリ
+#[derive(Debug, Clone, Deserialize)]
+pub struct LanguageConfig {
+    /// ソースを書き出すときのファイル名 (例: main.cpp, Main.java)
+    pub source_file: String,
+    /// コンパイルコマンド (インタプリタ言語なら省略)
+    #[serde(default)]
+    pub compile: Option<Vec<String>>,
+    /// 実行コマンド
+    pub run: Vec<String>,
+    /// 実行時に RLIMIT_AS でメモリを制限するか
+    /// (JVM や Go のように仮想メモリを大きく予約する処理系では false にする)
+    #[serde(default = "default_true")]
+    pub limit_address_space: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl LanguageConfig {
+    fn new(source_file: &str, compile: Option<&[&str]>, run: &[&str]) -> Self {
+        let to_vec = |args: &[&str]| args.iter().map(|a| a.to_string()).collect();
+        Self {
+            source_file: source_file.to_string(),
+            compile: compile.map(to_vec),
+            run: to_vec(run),
+            limit_address_space: true,
+        }
+    }
+}
+
+/// 既定で使える言語 (AtCoder のジャッジに近いオプションにしておく)
+fn default_languages() -> BTreeMap<String, LanguageConfig> {
+    BTreeMap::from([
+        (
+            "cpp".to_string(),
+            LanguageConfig::new(
+                "main.cpp",
+                Some(&["g++", "-std=gnu++20", "-O2", "-o", "{exe}", "{src}"]),
+                &["{exe}"],
+            ),
+        ),
+        (
+            "c".to_string(),
+            LanguageConfig::new(
+                "main.c",
+                Some(&["gcc", "-std=gnu11", "-O2", "-o", "{exe}", "{src}", "-lm"]),
+                &["{exe}"],
+            ),
+        ),
+        (
+            "rust".to_string(),
+            LanguageConfig::new(
+                "main.rs",
+                Some(&["rustc", "--edition", "2021", "-O", "-o", "{exe}", "{src}"]),
+                &["{exe}"],
+            ),
+        ),
+        (
+            "python".to_string(),
+            LanguageConfig::new("main.py", None, &["python3", "{src}"]),
+        ),
+        (
+            "pypy".to_string(),
+            LanguageConfig::new("main.py", None, &["pypy3", "{src}"]),
+        ),
+    ])
+}
+
+impl Config {
+    /// 設定ファイルと環境変数から設定を読み込む
+    pub fn load() -> anyhow::Result<Self> {
+        let mut config = match config_path() {
+            Some(path) if path.exists() => {
+                let text = std::fs::read_to_string(&path)
+                    .with_context(|| format!("Failed to read {}", path.display()))?;
+                let mut config: Config = serde_json::from_str(&text)
+                    .with_context(|| format!("Invalid config file {}", path.display()))?;
+                // 書かれていない言語は既定のものを使う
+                let mut languages = default_languages();
+                languages.append(&mut config.languages);
+                config.languages = languages;
+                config
+            }
+            _ => Config::default(),
+        };
+
+        if let Ok(lang) = std::env::var("ATCODER_MCP_LANG") {
+            config.default_lang = lang
+                .parse()
+                .context("Invalid ATCODER_MCP_LANG (expected ja, en or both)")?;
+        }
+
+        Ok(config)
+    }
+}
+
+/// 設定ファイルの場所
+fn config_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os("ATCODER_MCP_CONFIG") {
+        return Some(PathBuf::from(path));
+    }
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("atcoder-mcp").join("config.json"))
+}
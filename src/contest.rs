@@ -0,0 +1,720 @@
+//! コンテストのページを構造化したモデル
+//!
+//! 問題IDはコンテストIDから推測できるとは限らない (abc042 の問題が arc058_a だったり、
+//! ARC と AGC で問題を共有していたりする)。問題一覧のページから記号と問題IDの対応を取る。
+//! コンテストのトップページと `/contests/` の一覧からは、開催時間や Rated 対象を読む。
+
+use crate::problem::{parse_memory_limit, parse_time_limit};
+use crate::schema::SchemaType;
+use crate::time;
+use scraper::{ElementRef, Html, Selector, node::Node};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+/// AtCoder の日時はすべて日本時間 (UTC+9)
+const JST_OFFSET_SECS: i64 = 9 * 3600;
+
+/// これより長く開いているコンテストは常設コンテストとみなす
+const PERMANENT_SECS: i64 = 365 * 86400;
+
+tool_output! {
+    /// 問題一覧の1行
+    pub struct ContestTask {
+        /// 問題の記号 (例: A)
+        letter: String,
+        /// 問題名 (例: Tomorrow)
+        title: String,
+        /// 問題ID (例: abc335_a)
+        task_id: String,
+        /// 実行時間制限 (ミリ秒)
+        time_limit_ms: Option<u64>,
+        /// メモリ制限 (MB)
+        memory_limit_mb: Option<u64>,
+        /// 問題ページの URL
+        url: String,
+    }
+}
+
+tool_output! {
+    /// コンテストの問題一覧
+    pub struct ContestTasks {
+        /// コンテストID
+        contest_id: String,
+        /// 問題一覧ページの URL
+        url: String,
+        /// 問題 (記号の順)
+        tasks: Vec<ContestTask>,
+    }
+}
+
+impl ContestTasks {
+    /// 記号 (A, B, ..., Ex) から問題を探す。大文字小文字は区別しない
+    pub fn find_by_letter(&self, letter: &str) -> Option<&ContestTask> {
+        self.tasks
+            .iter()
+            .find(|task| task.letter.eq_ignore_ascii_case(letter.trim()))
+    }
+
+    /// LLM にそのまま見せる Markdown の表
+    pub fn to_markdown(&self) -> String {
+        let mut lines = vec![
+            format!("# {} の問題一覧", self.contest_id),
+            String::new(),
+            "| 記号 | 問題名 | 問題ID | 実行時間制限 | メモリ制限 |".to_string(),
+            "| --- | --- | --- | --- | --- |".to_string(),
+        ];
+        for task in &self.tasks {
+            let time = task
+                .time_limit_ms
+                .map_or_else(|| "-".to_string(), |ms| format!("{} ms", ms));
+            let memory = task
+                .memory_limit_mb
+                .map_or_else(|| "-".to_string(), |mb| format!("{} MB", mb));
+            lines.push(format!(
+                "| {} | [{}]({}) | {} | {} | {} |",
+                task.letter,
+                task.title.replace('|', "\\|"),
+                task.url,
+                task.task_id,
+                time,
+                memory
+            ));
+        }
+        lines.join("\n")
+    }
+}
+
+/// 問題一覧のページ (`/contests/{contest_id}/tasks`) を解析する
+pub fn parse_tasks_page(html: &str, contest_id: &str, url: &str) -> anyhow::Result<ContestTasks> {
+    let document = Html::parse_document(html);
+    let selector = Selector::parse("#main-container table tbody tr").unwrap();
+    let tasks: Vec<ContestTask> = document
+        .select(&selector)
+        .filter_map(parse_task_row)
+        .collect();
+    if tasks.is_empty() {
+        anyhow::bail!(
+            "Could not find tasks of {}. The contest may not have started yet.",
+            contest_id
+        );
+    }
+    Ok(ContestTasks {
+        contest_id: contest_id.to_string(),
+        url: url.to_string(),
+        tasks,
+    })
+}
+
+/// 表の1行: 記号 / 問題名 / 実行時間制限 / メモリ制限 / 提出
+fn parse_task_row(row: ElementRef) -> Option<ContestTask> {
+    let cells: Vec<ElementRef> = row
+        .children()
+        .filter_map(ElementRef::wrap)
+        .filter(|e| e.value().name() == "td")
+        .collect();
+    let link_selector = Selector::parse("a").unwrap();
+    let href = cells
+        .first()?
+        .select(&link_selector)
+        .find_map(|a| a.value().attr("href"))
+        .filter(|href| href.contains("/tasks/"))?;
+    let path = href.split(['?', '#']).next()?;
+    let task_id = path.rsplit('/').next().filter(|id| !id.is_empty())?;
+
+    let text = |cell: &ElementRef| {
+        cell.text()
+            .collect::<String>()
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    let letter = text(cells.first()?);
+    let title = cells.get(1).map(text).unwrap_or_default();
+    // 制限の列は言語やコンテストによって並びが変わりうるので、読めるものを探す
+    let time_limit_ms = cells
+        .iter()
+        .skip(2)
+        .find_map(|c| parse_time_limit(&text(c)));
+    let memory_limit_mb = cells
+        .iter()
+        .skip(2)
+        .find_map(|c| parse_memory_limit(&text(c)));
+
+    Some(ContestTask {
+        letter,
+        title,
+        task_id: task_id.to_string(),
+        time_limit_ms,
+        memory_limit_mb,
+        url: if path.starts_with('/') {
+            format!("https://atcoder.jp{}", path)
+        } else {
+            path.to_string()
+        },
+    })
+}
+
+/// 問題IDではなく記号 (A, b, Ex など) が渡されたか
+pub fn looks_like_letter(problem_id: &str) -> bool {
+    let id = problem_id.trim();
+    (1..=2).contains(&id.len()) && id.chars().all(|c| c.is_ascii_alphabetic())
+}
+
+/// コンテストの状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContestStatus {
+    /// 開始前
+    Upcoming,
+    /// 開催中
+    Running,
+    /// 終了済み
+    Archived,
+    /// 常設コンテスト (practice など、期限なく開いているもの)
+    Permanent,
+    /// 開催時刻が読めず判断できない (ページの構成が変わったときなど)
+    Unknown,
+}
+
+impl SchemaType for ContestStatus {
+    fn schema() -> Value {
+        json!({ "type": "string", "enum": ["upcoming", "running", "archived", "permanent", "unknown"] })
+    }
+}
+
+impl ContestStatus {
+    /// 開始・終了時刻から状態を決める
+    fn at(now: i64, start: i64, end: i64) -> Self {
+        if now < start {
+            Self::Upcoming
+        } else if end - start >= PERMANENT_SECS {
+            Self::Permanent
+        } else if now < end {
+            Self::Running
+        } else {
+            Self::Archived
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Upcoming => "開始前",
+            Self::Running => "開催中",
+            Self::Archived => "終了",
+            Self::Permanent => "常設",
+            Self::Unknown => "不明",
+        }
+    }
+}
+
+tool_output! {
+    /// Rated 対象の範囲
+    pub struct RatedRange {
+        /// ページに書かれている表記 (例: ~ 1999, All, -)
+        text: String,
+        /// Rated なコンテストか
+        rated: bool,
+        /// 対象となるレーティングの下限
+        min: Option<u32>,
+        /// 対象となるレーティングの上限
+        max: Option<u32>,
+    }
+}
+
+impl RatedRange {
+    /// 「~ 1999」「1200 - 2799」「All」「-」を読む
+    pub fn parse(text: &str) -> Self {
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        let lower = text.to_lowercase();
+        let unrated = text.is_empty()
+            || text == "-"
+            || lower.contains("unrated")
+            || text.contains("対象外")
+            || text == "×";
+        if unrated {
+            return Self {
+                text,
+                rated: false,
+                min: None,
+                max: None,
+            };
+        }
+        let (min, max) = match text.split_once(['~', '-', '〜']) {
+            Some((min, max)) => (min.trim().parse().ok(), max.trim().parse().ok()),
+            None => (None, None),
+        };
+        Self {
+            text,
+            rated: true,
+            min,
+            max,
+        }
+    }
+
+    /// このレーティングの人にとって Rated か
+    pub fn includes(&self, rating: u32) -> bool {
+        self.rated
+            && self.min.is_none_or(|min| min <= rating)
+            && self.max.is_none_or(|max| rating <= max)
+    }
+}
+
+tool_output! {
+    /// コンテストの情報
+    pub struct Contest {
+        /// コンテストID (例: abc335)
+        contest_id: String,
+        /// コンテスト名
+        name: String,
+        /// コンテストのトップページの URL
+        url: String,
+        /// 状態 (upcoming / running / archived / permanent / unknown)
+        status: ContestStatus,
+        /// 開始時刻 (例: 2024-01-06T21:00:00+09:00)
+        start_time: Option<String>,
+        /// 終了時刻 (例: 2024-01-06T22:40:00+09:00)
+        end_time: Option<String>,
+        /// コンテスト時間 (分)
+        duration_minutes: Option<u64>,
+        /// Rated 対象
+        rated_range: Option<RatedRange>,
+        /// 誤答1回あたりのペナルティ (分)。一覧から読んだときは分からない
+        penalty_minutes: Option<u64>,
+        /// rating を指定したとき、その人にとって Rated か
+        rated_for_you: Option<bool>,
+    }
+}
+
+impl Contest {
+    /// 指定されたレーティングで Rated かを埋める
+    pub fn with_rating(mut self, rating: Option<u32>) -> Self {
+        self.rated_for_you = rating.map(|rating| {
+            self.rated_range
+                .as_ref()
+                .is_some_and(|range| range.includes(rating))
+        });
+        self
+    }
+
+    /// LLM にそのまま見せる Markdown
+    pub fn to_markdown(&self) -> String {
+        let mut lines = vec![
+            format!("# {} ({})", self.name, self.contest_id),
+            String::new(),
+            format!("- 状態: {}", self.status.label()),
+        ];
+        if let Some(start) = &self.start_time {
+            lines.push(format!("- 開始: {}", start));
+        }
+        if let Some(end) = &self.end_time {
+            lines.push(format!("- 終了: {}", end));
+        }
+        if let Some(minutes) = self.duration_minutes {
+            lines.push(format!("- 時間: {} 分", minutes));
+        }
+        if let Some(range) = &self.rated_range {
+            lines.push(format!("- Rated 対象: {}", range.text));
+        }
+        if let Some(penalty) = self.penalty_minutes {
+            lines.push(format!("- ペナルティ: {} 分", penalty));
+        }
+        if let Some(rated) = self.rated_for_you {
+            lines.push(format!(
+                "- あなたにとって: {}",
+                if rated { "Rated" } else { "Unrated" }
+            ));
+        }
+        lines.push(format!("- URL: {}", self.url));
+        lines.join("\n")
+    }
+}
+
+tool_output! {
+    /// コンテストの一覧 (`/contests/`)
+    pub struct ContestSchedule {
+        /// 取得した時刻 (日本時間)
+        now: String,
+        /// 開催中・開始前・最近終わった・常設のコンテスト
+        contests: Vec<Contest>,
+    }
+}
+
+impl ContestSchedule {
+    /// LLM にそのまま見せる Markdown の表 (状態ごと)
+    pub fn to_markdown(&self) -> String {
+        let mut lines = vec![format!("現在時刻: {}", self.now)];
+        let with_rating = self.contests.iter().any(|c| c.rated_for_you.is_some());
+        for status in [
+            ContestStatus::Running,
+            ContestStatus::Upcoming,
+            ContestStatus::Archived,
+            ContestStatus::Permanent,
+            ContestStatus::Unknown,
+        ] {
+            let contests: Vec<_> = self
+                .contests
+                .iter()
+                .filter(|c| c.status == status)
+                .collect();
+            if contests.is_empty() {
+                continue;
+            }
+            lines.push(String::new());
+            lines.push(format!("## {}", status.label()));
+            lines.push(String::new());
+            let mut header = "| 開始 | コンテスト | 時間 | Rated 対象 |".to_string();
+            let mut rule = "| --- | --- | --- | --- |".to_string();
+            if with_rating {
+                header.push_str(" あなた |");
+                rule.push_str(" --- |");
+            }
+            lines.push(header);
+            lines.push(rule);
+            for contest in contests {
+                let mut row = format!(
+                    "| {} | [{}]({}) ({}) | {} | {} |",
+                    contest.start_time.as_deref().unwrap_or("-"),
+                    contest.name.replace('|', "\\|"),
+                    contest.url,
+                    contest.contest_id,
+                    contest
+                        .duration_minutes
+                        .map_or_else(|| "-".to_string(), |m| format!("{} 分", m)),
+                    contest
+                        .rated_range
+                        .as_ref()
+                        .map_or("-", |r| r.text.as_str()),
+                );
+                if let Some(rated) = contest.rated_for_you {
+                    row.push_str(if rated { " Rated |" } else { " Unrated |" });
+                }
+                lines.push(row);
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+/// ページに埋め込まれている時刻 (`var startTime = moment("...")` / `var endTime = ...`)
+pub fn embedded_time(body: &str, name: &str) -> Option<i64> {
+    let rest = &body[body.find(&format!("var {}", name))?..];
+    let start = rest.find('"')? + 1;
+    let end = start + rest[start..].find('"')?;
+    time::parse_datetime(&rest[start..end])
+}
+
+/// コンテストのトップページ (`/contests/{contest_id}`) を解析する
+pub fn parse_contest_page(html: &str, contest_id: &str, now: i64) -> anyhow::Result<Contest> {
+    let document = Html::parse_document(html);
+    let selector = Selector::parse("#main-container").unwrap();
+    let Some(main) = document.select(&selector).next() else {
+        anyhow::bail!("Could not find contest information of {}.", contest_id)
+    };
+
+    let name = select_text(main, ".contest-title")
+        .or_else(|| {
+            let title = select_text(document.root_element(), "title")?;
+            Some(title.trim_end_matches(" - AtCoder").to_string())
+        })
+        .unwrap_or_else(|| contest_id.to_string());
+
+    // 時刻はスクリプトの startTime / endTime が確実。無ければ表示されている時刻を使う
+    let time_selector = Selector::parse("time.fixtime-full").unwrap();
+    let shown: Vec<i64> = main
+        .select(&time_selector)
+        .filter_map(|t| time::parse_datetime(&t.text().collect::<String>()))
+        .collect();
+    let start = embedded_time(html, "startTime").or_else(|| shown.first().copied());
+    let end = embedded_time(html, "endTime").or_else(|| shown.get(1).copied());
+
+    let rated_range = labeled_value(main, &["Rated対象", "Rated 対象", "Rated Range"])
+        .map(|text| RatedRange::parse(&text));
+    let penalty_minutes = labeled_value(main, &["ペナルティ", "Penalty"]).and_then(|text| {
+        let digits: String = text.chars().take_while(char::is_ascii_digit).collect();
+        match digits.parse() {
+            Ok(minutes) => Some(minutes),
+            // 「なし」「None」
+            Err(_) if text.contains("なし") || text.to_lowercase().contains("none") => Some(0),
+            Err(_) => None,
+        }
+    });
+
+    Ok(contest_from_times(
+        contest_id,
+        name,
+        start,
+        end,
+        now,
+        rated_range,
+        penalty_minutes,
+    ))
+}
+
+/// コンテスト一覧のページ (`/contests/`) を解析する
+pub fn parse_contests_page(html: &str, now: i64) -> anyhow::Result<ContestSchedule> {
+    let document = Html::parse_document(html);
+    let tables = [
+        ("#contest-table-action", ContestStatus::Running),
+        ("#contest-table-upcoming", ContestStatus::Upcoming),
+        ("#contest-table-recent", ContestStatus::Archived),
+        ("#contest-table-permanent", ContestStatus::Permanent),
+    ];
+    let mut contests = Vec::new();
+    for (id, status) in tables {
+        let selector = Selector::parse(&format!("{} tbody tr", id)).unwrap();
+        contests.extend(
+            document
+                .select(&selector)
+                .filter_map(|row| parse_contest_row(row, status, now)),
+        );
+    }
+    if contests.is_empty() {
+        anyhow::bail!("Could not find contests in the contest list page.");
+    }
+    Ok(ContestSchedule {
+        now: time::format_datetime(now, JST_OFFSET_SECS),
+        contests,
+    })
+}
+
+/// 一覧の1行: 開始時刻 / コンテスト名 / 時間 (hh:mm) / Rated 対象 (常設は名前と Rated 対象だけ)
+fn parse_contest_row(row: ElementRef, section: ContestStatus, now: i64) -> Option<Contest> {
+    let cells: Vec<ElementRef> = row
+        .children()
+        .filter_map(ElementRef::wrap)
+        .filter(|e| e.value().name() == "td")
+        .collect();
+    let link_selector = Selector::parse("a").unwrap();
+    let (name_index, link) = cells.iter().enumerate().find_map(|(i, cell)| {
+        cell.select(&link_selector)
+            .find(|a| {
+                a.value()
+                    .attr("href")
+                    .is_some_and(|h| h.contains("/contests/"))
+            })
+            .map(|a| (i, a))
+    })?;
+    let href = link.value().attr("href")?;
+    let contest_id = href
+        .split(['?', '#'])
+        .next()?
+        .split("/contests/")
+        .nth(1)?
+        .split('/')
+        .next()
+        .filter(|id| !id.is_empty())?;
+
+    let time_selector = Selector::parse("time").unwrap();
+    let start = row
+        .select(&time_selector)
+        .find_map(|t| time::parse_datetime(&t.text().collect::<String>()));
+    let duration = cells
+        .iter()
+        .skip(name_index + 1)
+        .find_map(|c| parse_duration(&cell_text(*c)));
+    let rated_range = cells
+        .last()
+        .filter(|_| cells.len() > name_index + 1)
+        .map(|c| RatedRange::parse(&cell_text(*c)));
+
+    let end = start.zip(duration).map(|(start, secs)| start + secs);
+    let mut contest = contest_from_times(
+        contest_id,
+        collapsed(&link.text().collect::<String>()),
+        start,
+        end,
+        now,
+        rated_range,
+        None,
+    );
+    // 時刻が読めない行 (常設など) は載っていた表で判断する
+    if contest.status == ContestStatus::Unknown {
+        contest.status = section;
+    }
+    Some(contest)
+}
+
+/// 「01:40」「240:00」を秒にする
+fn parse_duration(text: &str) -> Option<i64> {
+    let (hours, minutes) = text.trim().split_once(':')?;
+    let hours: i64 = hours.parse().ok()?;
+    let minutes: i64 = minutes.parse().ok()?;
+    Some((hours * 60 + minutes) * 60)
+}
+
+fn contest_from_times(
+    contest_id: &str,
+    name: String,
+    start: Option<i64>,
+    end: Option<i64>,
+    now: i64,
+    rated_range: Option<RatedRange>,
+    penalty_minutes: Option<u64>,
+) -> Contest {
+    let status = match (start, end) {
+        (Some(start), Some(end)) => ContestStatus::at(now, start, end),
+        // 終わったとみなすと開催中の問題の解説を出してしまうので、分からないままにする
+        _ => ContestStatus::Unknown,
+    };
+    let duration_minutes = start
+        .zip(end)
+        .filter(|(start, end)| end >= start)
+        .map(|(start, end)| ((end - start) / 60) as u64);
+    Contest {
+        contest_id: contest_id.to_string(),
+        name,
+        url: format!("https://atcoder.jp/contests/{}", contest_id),
+        status,
+        start_time: start.map(|t| time::format_datetime(t, JST_OFFSET_SECS)),
+        end_time: end.map(|t| time::format_datetime(t, JST_OFFSET_SECS)),
+        duration_minutes,
+        rated_range,
+        penalty_minutes,
+        rated_for_you: None,
+    }
+}
+
+/// 「Rated対象: ~ 1999」のようにラベルの後ろに書かれた値を探す
+///
+/// ラベルと値が別の要素に分かれていることがあるので、ラベルを含むテキストから
+/// 親要素へたどって、ラベルの後ろに値が出てくるところまで広げる。
+fn labeled_value(root: ElementRef, labels: &[&str]) -> Option<String> {
+    for node in root.descendants() {
+        let Node::Text(text) = node.value() else {
+            continue;
+        };
+        let Some(label) = labels.iter().find(|l| text.contains(*l)) else {
+            continue;
+        };
+        let mut element = node.parent().and_then(ElementRef::wrap);
+        while let Some(current) = element {
+            let full = current.text().collect::<String>();
+            if let Some(value) = value_after(&full, label) {
+                return Some(value);
+            }
+            if current == root {
+                break;
+            }
+            element = current.parent().and_then(ElementRef::wrap);
+        }
+    }
+    None
+}
+
+/// ラベルの後ろの値 (「:」を飛ばし、次の「|」か改行まで)
+fn value_after(text: &str, label: &str) -> Option<String> {
+    let rest = &text[text.find(label)? + label.len()..];
+    let rest = rest
+        .trim_start()
+        .trim_start_matches([':', '：'])
+        .trim_start();
+    let value = rest.split(['|', '\n']).next().unwrap_or("");
+    let value = collapsed(value);
+    (!value.is_empty()).then_some(value)
+}
+
+fn select_text(root: ElementRef, selector: &str) -> Option<String> {
+    let selector = Selector::parse(selector).unwrap();
+    root.select(&selector)
+        .next()
+        .map(|e| collapsed(&e.text().collect::<String>()))
+        .filter(|t| !t.is_empty())
+}
+
+fn cell_text(cell: ElementRef) -> String {
+    collapsed(&cell.text().collect::<String>())
+}
+
+fn collapsed(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_rated_ranges() {
+        let range = RatedRange::parse(" ~ 1999 ");
+        assert_eq!(range.text, "~ 1999");
+        assert!(range.rated);
+        assert_eq!((range.min, range.max), (None, Some(1999)));
+
+        let range = RatedRange::parse("1200 - 2799");
+        assert!(range.rated);
+        assert_eq!((range.min, range.max), (Some(1200), Some(2799)));
+
+        let range = RatedRange::parse("1600 ~");
+        assert_eq!((range.min, range.max), (Some(1600), None));
+
+        let range = RatedRange::parse("All");
+        assert!(range.rated);
+        assert_eq!((range.min, range.max), (None, None));
+    }
+
+    #[test]
+    fn parses_unrated_ranges() {
+        for text in ["-", "", "Unrated", "対象外", "×"] {
+            let range = RatedRange::parse(text);
+            assert!(!range.rated, "{:?} should be unrated", text);
+            assert!(!range.includes(1500));
+        }
+    }
+
+    #[test]
+    fn rated_range_includes_rating() {
+        let range = RatedRange::parse("~ 1999");
+        assert!(range.includes(0));
+        assert!(range.includes(1999));
+        assert!(!range.includes(2000));
+
+        let range = RatedRange::parse("1200 - 2799");
+        assert!(!range.includes(1199));
+        assert!(range.includes(1200));
+        assert!(range.includes(2799));
+        assert!(!range.includes(2800));
+
+        assert!(RatedRange::parse("All").includes(4000));
+    }
+
+    #[test]
+    fn parses_duration() {
+        assert_eq!(parse_duration("01:40"), Some(100 * 60));
+        assert_eq!(parse_duration(" 240:00 "), Some(240 * 3600));
+        assert_eq!(parse_duration("00:05"), Some(5 * 60));
+        assert_eq!(parse_duration("1h40m"), None);
+        assert_eq!(parse_duration("-"), None);
+    }
+
+    #[test]
+    fn status_follows_schedule() {
+        let (start, end) = (1000, 1000 + 100 * 60);
+        assert_eq!(ContestStatus::at(0, start, end), ContestStatus::Upcoming);
+        assert_eq!(ContestStatus::at(start, start, end), ContestStatus::Running);
+        assert_eq!(ContestStatus::at(end, start, end), ContestStatus::Archived);
+        assert_eq!(
+            ContestStatus::at(start, start, start + PERMANENT_SECS),
+            ContestStatus::Permanent
+        );
+    }
+
+    #[test]
+    fn missing_times_make_status_unknown() {
+        let contest =
+            contest_from_times("abc999", "ABC 999".to_string(), None, None, 0, None, None);
+        assert_eq!(contest.status, ContestStatus::Unknown);
+        assert_eq!(contest.duration_minutes, None);
+
+        let contest = contest_from_times(
+            "abc999",
+            "ABC 999".to_string(),
+            Some(0),
+            Some(6000),
+            3000,
+            None,
+            None,
+        );
+        assert_eq!(contest.status, ContestStatus::Running);
+        assert_eq!(contest.duration_minutes, Some(100));
+    }
+}
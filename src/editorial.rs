@@ -0,0 +1,499 @@
+//! 解説ページを構造化したモデル
+//!
+//! 問題ごとの解説一覧 (`/tasks/{problem_id}/editorial`) は公式解説とユーザー解説のリンクが並んだページで、
+//! 本文はリンク先の個別ページ (`/contests/{contest_id}/editorial/{id}`) にある。
+//! 一覧は `EditorialIndex` に分解し、個別ページは数式やコードを保ったまま Markdown にする。
+
+use crate::atcoder::Lang;
+use crate::markdown;
+use scraper::{ElementRef, Html, Selector};
+
+const ORIGIN: &str = "https://atcoder.jp";
+
+tool_output! {
+    /// 解説一覧の1件
+    pub struct EditorialEntry {
+        /// リンクの文字列 (例: 解説)
+        title: String,
+        /// 解説の URL
+        url: String,
+        /// 公式解説なら true、ユーザー解説なら false
+        official: bool,
+        /// 書いた人の AtCoder ID
+        author: Option<String>,
+        /// 解説の言語 (ja / en)
+        language: Option<String>,
+    }
+}
+
+tool_output! {
+    /// 問題ごとの解説一覧
+    pub struct EditorialIndex {
+        /// コンテストID
+        contest_id: String,
+        /// 問題ID
+        problem_id: String,
+        /// 解説一覧ページの URL
+        url: String,
+        /// 解説 (ページに載っている順)
+        entries: Vec<EditorialEntry>,
+    }
+}
+
+tool_output! {
+    /// 個別の解説ページ
+    pub struct EditorialArticle {
+        /// 解説のタイトル
+        title: String,
+        /// 解説の URL
+        url: String,
+        /// 書いた人の AtCoder ID
+        author: Option<String>,
+        /// 本文 (Markdown)
+        markdown: String,
+    }
+}
+
+impl EditorialIndex {
+    /// 本文を読みに行く公式解説 (指定の言語のものがあればそれ、無ければ先頭)
+    /// atcoder.jp の外にあるものは本文を取得できないので除く
+    pub fn official(&self, lang: Lang) -> Option<&EditorialEntry> {
+        let wanted = match lang {
+            Lang::En => "en",
+            Lang::Ja | Lang::Both => "ja",
+        };
+        let readable: Vec<_> = self
+            .entries
+            .iter()
+            .filter(|e| e.official && article_url(&e.url).is_ok())
+            .collect();
+        readable
+            .iter()
+            .find(|e| e.language.as_deref() == Some(wanted))
+            .or_else(|| readable.first())
+            .copied()
+    }
+
+    /// LLM にそのまま見せる Markdown の一覧
+    pub fn to_markdown(&self) -> String {
+        let mut lines = vec![format!("# {} の解説一覧", self.problem_id)];
+        for (heading, official) in [("公式解説", true), ("ユーザー解説", false)] {
+            let entries: Vec<_> = self
+                .entries
+                .iter()
+                .filter(|e| e.official == official)
+                .collect();
+            lines.push(String::new());
+            lines.push(format!("## {}", heading));
+            if entries.is_empty() {
+                lines.push("(なし)".to_string());
+            }
+            for entry in entries {
+                let mut line = format!("- [{}]({})", entry.title, entry.url);
+                if let Some(author) = &entry.author {
+                    line.push_str(&format!(" by {}", author));
+                }
+                if let Some(language) = &entry.language {
+                    line.push_str(&format!(" ({})", language));
+                }
+                lines.push(line);
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+impl EditorialArticle {
+    /// タイトルと著者を付けた Markdown
+    pub fn to_markdown(&self) -> String {
+        let mut text = format!("# {}\n\n", self.title);
+        if let Some(author) = &self.author {
+            text.push_str(&format!("by {}\n\n", author));
+        }
+        text.push_str(&self.markdown);
+        text
+    }
+}
+
+/// 一覧の見出しから分かる解説の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Official,
+    User,
+    Other,
+}
+
+fn classify(heading: &str) -> Section {
+    let heading = heading.to_lowercase();
+    if heading.contains("公式") || heading.contains("official") {
+        Section::Official
+    } else if heading.contains("ユーザー") || heading.contains("user") {
+        Section::User
+    } else {
+        Section::Other
+    }
+}
+
+/// 解説一覧のページを解析する
+pub fn parse_editorial_index(
+    html: &str,
+    contest_id: &str,
+    problem_id: &str,
+    url: &str,
+) -> anyhow::Result<EditorialIndex> {
+    let document = Html::parse_document(html);
+    let selector = Selector::parse("#main-container").unwrap();
+    let Some(main) = document.select(&selector).next() else {
+        anyhow::bail!("Could not find content in editorial page.")
+    };
+
+    // 見出し (公式解説 / ユーザー解説) と解説のリンクが並んでいるので、文書順に見ていく
+    let mut section = None;
+    let mut entries = Vec::new();
+    for element in main.descendants().filter_map(ElementRef::wrap) {
+        match element.value().name() {
+            "h2" | "h3" | "h4" => section = Some(classify(&collapsed_text(element))),
+            "li" => {
+                let Some(entry) = parse_entry(element, section) else {
+                    continue;
+                };
+                entries.push(entry);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(EditorialIndex {
+        contest_id: contest_id.to_string(),
+        problem_id: problem_id.to_string(),
+        url: url.to_string(),
+        entries,
+    })
+}
+
+/// 一覧の1行 (`<li>`): 解説へのリンク、著者、言語の旗
+fn parse_entry(item: ElementRef, section: Option<Section>) -> Option<EditorialEntry> {
+    let anchors: Vec<_> = item
+        .descendants()
+        .filter_map(ElementRef::wrap)
+        .filter(|e| e.value().name() == "a")
+        .collect();
+    let is_user_link = |a: &ElementRef| {
+        a.value().classes().any(|c| c == "username")
+            || a.value()
+                .attr("href")
+                .is_some_and(|h| h.contains("/users/"))
+    };
+
+    let link = anchors.iter().find(|a| !is_user_link(a))?;
+    let href = link.value().attr("href")?;
+    // 見出しの後ろにないリンクは、解説ページへのものだけを拾う (タブなどのナビゲーションを除く)
+    match section {
+        Some(Section::Official | Section::User) => {}
+        _ if href.contains("/editorial/") => {}
+        _ => return None,
+    }
+
+    let title = collapsed_text(*link);
+    let author = anchors
+        .iter()
+        .find(|a| is_user_link(a))
+        .map(|a| collapsed_text(*a))
+        .filter(|name| !name.is_empty());
+    let language = item
+        .descendants()
+        .filter_map(ElementRef::wrap)
+        .find(|e| e.value().name() == "img")
+        .and_then(flag_language);
+
+    Some(EditorialEntry {
+        title: if title.is_empty() {
+            "解説".to_string()
+        } else {
+            title
+        },
+        url: absolute_url(href),
+        official: section == Some(Section::Official),
+        author,
+        language,
+    })
+}
+
+/// 旗の画像 (`.../flag/JP.png` や `.../flag-lang/en.png`) から解説の言語を読む
+fn flag_language(img: ElementRef) -> Option<String> {
+    let src = img.value().attr("src")?;
+    let file = src.rsplit('/').next()?;
+    let stem = file.split('.').next()?.to_lowercase();
+    let language = match stem.as_str() {
+        "jp" | "ja" => "ja",
+        "us" | "gb" | "uk" | "en" => "en",
+        "" => return None,
+        other => other,
+    };
+    Some(language.to_string())
+}
+
+/// 個別の解説ページを解析する
+pub fn parse_editorial_article(html: &str, url: &str) -> anyhow::Result<EditorialArticle> {
+    let document = Html::parse_document(html);
+    let main_selector = Selector::parse("#main-container").unwrap();
+    let Some(main) = document.select(&main_selector).next() else {
+        anyhow::bail!("Could not find content in editorial page.")
+    };
+
+    // 本文は #editorial にある。無ければメインカラムごと変換する
+    let body_selector = Selector::parse("#editorial").unwrap();
+    let column_selector = Selector::parse(".col-sm-12").unwrap();
+    let body = main
+        .select(&body_selector)
+        .next()
+        .or_else(|| main.select(&column_selector).next())
+        .unwrap_or(main);
+
+    let title_selector = Selector::parse("h2").unwrap();
+    let title = main
+        .select(&title_selector)
+        .next()
+        .map(collapsed_text)
+        .filter(|t| !t.is_empty())
+        .unwrap_or_else(|| "解説".to_string());
+    let author_selector = Selector::parse("a.username").unwrap();
+    let author = main
+        .select(&author_selector)
+        .next()
+        .map(collapsed_text)
+        .filter(|a| !a.is_empty());
+
+    Ok(EditorialArticle {
+        title,
+        url: url.to_string(),
+        author,
+        markdown: markdown::to_markdown(body),
+    })
+}
+
+/// 解説の URL を検証して絶対 URL にする
+/// atcoder.jp の外 (ブログなど) のユーザー解説は取得しない
+pub fn article_url(url: &str) -> anyhow::Result<String> {
+    let (path, _) = article_path(url)?;
+    Ok(format!("{}{}", ORIGIN, path))
+}
+
+/// 解説の URL が属するコンテストのID (開催中かを確かめるため)
+pub fn article_contest_id(url: &str) -> anyhow::Result<String> {
+    let (_, contest_id) = article_path(url)?;
+    Ok(contest_id.to_string())
+}
+
+/// atcoder.jp の解説の URL を、パスとコンテストIDに分ける
+fn article_path(url: &str) -> anyhow::Result<(&str, &str)> {
+    let url = url.trim();
+    let path = ["https://atcoder.jp", "http://atcoder.jp", "//atcoder.jp"]
+        .iter()
+        .find_map(|origin| url.strip_prefix(origin))
+        .unwrap_or(url);
+    let segments: Vec<&str> = path
+        .split(['?', '#'])
+        .next()
+        .unwrap_or("")
+        .split('/')
+        .collect();
+    match segments.as_slice() {
+        ["", "contests", contest, "editorial", id] if !contest.is_empty() && !id.is_empty() => {
+            Ok((path, contest))
+        }
+        _ => anyhow::bail!(
+            "Not an AtCoder editorial URL: {} (expected https://atcoder.jp/contests/<contest_id>/editorial/<id>)",
+            url
+        ),
+    }
+}
+
+fn absolute_url(href: &str) -> String {
+    if href.starts_with("//") {
+        format!("https:{}", href)
+    } else if href.starts_with('/') {
+        format!("{}{}", ORIGIN, href)
+    } else {
+        href.to_string()
+    }
+}
+
+fn collapsed_text(element: ElementRef) -> String {
+    element
+        .text()
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INDEX: &str = r#"<div id="main-container">
+        <ul class="nav"><li><a href="/contests/abc335/tasks">問題</a></li></ul>
+        <h3>公式解説</h3>
+        <ul>
+          <li><img src="//img.atcoder.jp/assets/flag/JP.png"> <a href="/contests/abc335/editorial/9041">解説</a> by <a class="username" href="/users/evima">evima</a></li>
+          <li><img src="/public/img/flag-lang/en.png"> <a href="https://atcoder.jp/contests/abc335/editorial/9042">Editorial</a></li>
+        </ul>
+        <h3>ユーザー解説</h3>
+        <ul>
+          <li><a href="https://example.com/blog/abc335c">ブログ</a> by <a href="/users/someone">someone</a></li>
+        </ul>
+      </div>"#;
+
+    fn index() -> EditorialIndex {
+        parse_editorial_index(
+            INDEX,
+            "abc335",
+            "abc335_c",
+            "https://atcoder.jp/contests/abc335/tasks/abc335_c/editorial",
+        )
+        .unwrap()
+    }
+
+    fn entry(html: &str, section: Option<Section>) -> Option<EditorialEntry> {
+        let document = Html::parse_fragment(&format!("<ul>{}</ul>", html));
+        let selector = Selector::parse("li").unwrap();
+        parse_entry(document.select(&selector).next().unwrap(), section)
+    }
+
+    #[test]
+    fn parses_index_by_section() {
+        let index = index();
+        let summary: Vec<_> = index
+            .entries
+            .iter()
+            .map(|e| {
+                (
+                    e.url.as_str(),
+                    e.official,
+                    e.author.as_deref(),
+                    e.language.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (
+                    "https://atcoder.jp/contests/abc335/editorial/9041",
+                    true,
+                    Some("evima"),
+                    Some("ja")
+                ),
+                (
+                    "https://atcoder.jp/contests/abc335/editorial/9042",
+                    true,
+                    None,
+                    Some("en")
+                ),
+                (
+                    "https://example.com/blog/abc335c",
+                    false,
+                    Some("someone"),
+                    None
+                ),
+            ]
+        );
+        assert_eq!(index.entries[0].title, "解説");
+    }
+
+    #[test]
+    fn picks_official_editorial_by_language() {
+        let index = index();
+        assert_eq!(index.official(Lang::Ja).unwrap().url, index.entries[0].url);
+        assert_eq!(index.official(Lang::En).unwrap().url, index.entries[1].url);
+    }
+
+    #[test]
+    fn rejects_index_without_main_container() {
+        assert!(parse_editorial_index("<html></html>", "abc335", "abc335_c", "").is_err());
+    }
+
+    #[test]
+    fn parses_entry_outside_sections_only_for_editorial_links() {
+        let link = r#"<li><a href="/contests/abc335/editorial/9041">解説</a></li>"#;
+        let parsed = entry(link, None).unwrap();
+        assert!(!parsed.official);
+        assert_eq!(
+            parsed.url,
+            "https://atcoder.jp/contests/abc335/editorial/9041"
+        );
+        assert!(
+            entry(
+                r#"<li><a href="/contests/abc335/tasks">問題</a></li>"#,
+                None
+            )
+            .is_none()
+        );
+        assert!(entry(link, Some(Section::Other)).is_some());
+    }
+
+    #[test]
+    fn parses_entry_without_title_or_link() {
+        let parsed = entry(
+            r#"<li><a href="/contests/abc335/editorial/1"> </a><a class="username">x</a></li>"#,
+            Some(Section::User),
+        )
+        .unwrap();
+        assert_eq!(parsed.title, "解説");
+        assert_eq!(parsed.author.as_deref(), Some("x"));
+        assert!(entry(r#"<li><a class="username">x</a></li>"#, Some(Section::User)).is_none());
+    }
+
+    #[test]
+    fn parses_article() {
+        let html = r#"<div id="main-container"><div class="col-sm-12">
+            <h2>C - Loong Tracking 解説</h2>
+            <p>by <a class="username" href="/users/evima">evima</a></p>
+            <div id="editorial"><p>頭の位置を<strong>記録</strong>しておきます。</p></div>
+          </div></div>"#;
+        let article =
+            parse_editorial_article(html, "https://atcoder.jp/contests/abc335/editorial/9041")
+                .unwrap();
+        assert_eq!(article.title, "C - Loong Tracking 解説");
+        assert_eq!(article.author.as_deref(), Some("evima"));
+        assert!(article.markdown.contains("**記録**"));
+        assert!(!article.markdown.contains("evima"));
+        assert!(parse_editorial_article("<p>no main</p>", "").is_err());
+    }
+
+    #[test]
+    fn normalises_article_urls() {
+        let expected = "https://atcoder.jp/contests/abc335/editorial/9041";
+        for url in [
+            expected,
+            " http://atcoder.jp/contests/abc335/editorial/9041 ",
+            "//atcoder.jp/contests/abc335/editorial/9041",
+            "/contests/abc335/editorial/9041",
+        ] {
+            assert_eq!(article_url(url).unwrap(), expected, "{}", url);
+            assert_eq!(article_contest_id(url).unwrap(), "abc335");
+        }
+        assert_eq!(
+            article_path("https://atcoder.jp/contests/abc335/editorial/9041?lang=en").unwrap(),
+            ("/contests/abc335/editorial/9041?lang=en", "abc335")
+        );
+    }
+
+    #[test]
+    fn rejects_off_site_and_malformed_article_urls() {
+        for url in [
+            "https://example.com/contests/abc335/editorial/9041",
+            "https://atcoder.jp.example.com/contests/abc335/editorial/9041",
+            "https://atcoder.jp/contests/abc335/editorial",
+            "https://atcoder.jp/contests//editorial/9041",
+            "https://atcoder.jp/contests/abc335/tasks/abc335_c/editorial",
+            "https://atcoder.jp/contests/abc335/editorial/9041/extra",
+            "contests/abc335/editorial/9041",
+            "",
+        ] {
+            assert!(article_url(url).is_err(), "{}", url);
+            assert!(article_contest_id(url).is_err(), "{}", url);
+        }
+    }
+}
@@ -0,0 +1,337 @@
+//! 解説から段階的なヒントを作る
+//!
+//! いきなり解説を全部見せるとネタバレになるので、公式解説の本文を4段階に切り分ける。
+//! 1: 手法の分類 / 2: 鍵になる観察 / 3: 解法の流れ (見出しと各節の最初の一文) / 4: 解説全文。
+//! どこまで見せてよいかの管理は get_hint ツールの側で行う。
+
+/// ヒントの段階の数
+pub const MAX_LEVEL: u32 = 4;
+
+/// 段階ごとの名前
+pub fn level_name(level: u32) -> &'static str {
+    match level {
+        1 => "手法の分類",
+        2 => "鍵になる観察",
+        3 => "解法の流れ",
+        _ => "解説全文",
+    }
+}
+
+/// 手法の分類と、解説にそれが出てきたと判断するキーワード
+///
+/// 大文字だけの短い略語 (DP, BFS など) は "dp" のような無関係の部分文字列に
+/// 反応しないよう大文字小文字を区別し、それ以外は区別せずに探す。
+const TECHNIQUES: &[(&str, &[&str])] = &[
+    ("全探索", &["全探索", "brute force", "すべて試", "全て試"]),
+    ("二分探索", &["二分探索", "binary search", "にぶたん"]),
+    (
+        "動的計画法 (DP)",
+        &["DP", "動的計画", "dynamic programming", "メモ化"],
+    ),
+    (
+        "グラフ探索 (BFS / DFS)",
+        &[
+            "BFS",
+            "DFS",
+            "幅優先",
+            "深さ優先",
+            "breadth-first",
+            "depth-first",
+        ],
+    ),
+    (
+        "最短経路",
+        &[
+            "ダイクストラ",
+            "dijkstra",
+            "ワーシャル",
+            "warshall",
+            "ベルマン",
+            "bellman",
+        ],
+    ),
+    ("Union-Find", &["union-find", "unionfind", "DSU", "素集合"]),
+    ("累積和", &["累積和", "prefix sum", "いもす", "imos"]),
+    ("貪欲法", &["貪欲", "greedy"]),
+    ("尺取り法", &["尺取り", "two pointers", "two-pointer"]),
+    (
+        "セグメント木 / BIT",
+        &["セグメント木", "セグ木", "segment tree", "BIT", "fenwick"],
+    ),
+    (
+        "整数論",
+        &["素数", "約数", "素因数", "gcd", "lcm", "逆元", "prime"],
+    ),
+    (
+        "数え上げ",
+        &["二項係数", "組合せ", "組み合わせ", "binomial", "包除"],
+    ),
+    (
+        "文字列アルゴリズム",
+        &[
+            "ローリングハッシュ",
+            "rolling hash",
+            "z-algorithm",
+            "KMP",
+            "suffix array",
+        ],
+    ),
+    (
+        "データ構造 (スタック / キュー / ヒープ)",
+        &[
+            "deque",
+            "priority_queue",
+            "優先度付きキュー",
+            "スタック",
+            "stack",
+            "ヒープ",
+            "heap",
+        ],
+    ),
+    ("ソート", &["ソート", "sort"]),
+    (
+        "シミュレーション",
+        &["シミュレーション", "simulation", "愚直"],
+    ),
+];
+
+/// 段階 1 で見せる分類の数
+const MAX_TECHNIQUES: usize = 3;
+
+/// 段階 3 で見せる行 (見出しと文) の数
+const MAX_OUTLINE_LINES: usize = 12;
+
+/// 段階 3 で1文として見せる最大の文字数
+const MAX_SENTENCE_CHARS: usize = 160;
+
+/// 解説本文に出てくる手法の分類 (多く出てくる順)
+pub fn techniques(markdown: &str) -> Vec<&'static str> {
+    let lower = markdown.to_lowercase();
+    let mut found: Vec<(&str, usize)> = TECHNIQUES
+        .iter()
+        .map(|(name, keywords)| {
+            let count = keywords
+                .iter()
+                .map(|keyword| {
+                    if keyword.chars().all(|c| c.is_ascii_uppercase()) {
+                        markdown.matches(keyword).count()
+                    } else {
+                        lower.matches(&keyword.to_lowercase()).count()
+                    }
+                })
+                .sum();
+            (*name, count)
+        })
+        .filter(|(_, count)| *count > 0)
+        .collect();
+    // 同数なら表の順 (より具体的なものを先に並べてある)
+    found.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+    found
+        .into_iter()
+        .take(MAX_TECHNIQUES)
+        .map(|(name, _)| name)
+        .collect()
+}
+
+/// Markdown のブロック (空行区切り。コードブロックの中の空行では切らない)
+struct Block {
+    text: String,
+    code: bool,
+    heading: bool,
+}
+
+fn blocks(markdown: &str) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+    let mut fence: Option<&str> = None;
+
+    let mut flush = |current: &mut Vec<&str>| {
+        if current.is_empty() {
+            return;
+        }
+        let text = current.join("\n");
+        blocks.push(Block {
+            code: text.starts_with("```"),
+            heading: text.starts_with('#'),
+            text,
+        });
+        current.clear();
+    };
+
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+        match fence {
+            Some(open) => {
+                current.push(line);
+                if trimmed.starts_with(open) && trimmed.trim_end() == open {
+                    fence = None;
+                    flush(&mut current);
+                }
+            }
+            None if trimmed.starts_with("```") => {
+                flush(&mut current);
+                let ticks = trimmed.len() - trimmed.trim_start_matches('`').len();
+                fence = Some(&trimmed[..ticks]);
+                current.push(line);
+            }
+            None if line.trim().is_empty() => flush(&mut current),
+            None => current.push(line),
+        }
+    }
+    flush(&mut current);
+    blocks
+}
+
+/// 段階 2: 本文の最初の段落 (見出しとコードは除く)
+/// 解説はたいてい最初に鍵になる観察を述べ、そのあとで解法を組み立てる
+pub fn key_observation(markdown: &str) -> Option<String> {
+    blocks(markdown)
+        .into_iter()
+        .find(|block| !block.code && !block.heading)
+        .map(|block| block.text)
+}
+
+/// 段階 3: 見出しと、節ごとの最初の一文 (コードは除く)
+///
+/// 本文をそのまま見せると解説全文とほとんど変わらないので、骨組みだけにする。
+/// 見出しの無い解説は段落を節とみなす。
+pub fn outline(markdown: &str) -> Option<String> {
+    let blocks = blocks(markdown);
+    let has_headings = blocks.iter().any(|block| block.heading);
+    let mut lines = Vec::new();
+    // 今の節で一文を出したか
+    let mut sentence_shown = false;
+    for block in blocks.iter().filter(|block| !block.code) {
+        if block.heading {
+            lines.push(block.text.lines().next().unwrap_or_default().to_string());
+            sentence_shown = false;
+        } else if !has_headings || !sentence_shown {
+            let sentence = first_sentence(&block.text);
+            if !sentence.is_empty() {
+                lines.push(format!("- {}", sentence));
+                sentence_shown = true;
+            }
+        }
+        if lines.len() >= MAX_OUTLINE_LINES {
+            break;
+        }
+    }
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+/// 段落の最初の一文 (句点・改行まで。$...$ の中の . では切らない)
+fn first_sentence(text: &str) -> String {
+    let text = text.trim();
+    // 箇条書きや引用の記号は外す (outline の側で "- " を付け直す)
+    let text = ["- ", "* ", "> "]
+        .iter()
+        .find_map(|marker| text.strip_prefix(marker))
+        .unwrap_or(text)
+        .trim_start();
+    let mut in_math = false;
+    let mut end = text.len();
+    for (i, c) in text.char_indices() {
+        let next = text[i + c.len_utf8()..].chars().next();
+        match c {
+            '$' => in_math = !in_math,
+            '\n' if !in_math => {
+                end = i;
+                break;
+            }
+            '。' | '．' if !in_math => {
+                end = i + c.len_utf8();
+                break;
+            }
+            '.' | '!' | '?' if !in_math && next.is_none_or(char::is_whitespace) => {
+                end = i + 1;
+                break;
+            }
+            _ => {}
+        }
+    }
+    let sentence = text[..end].trim();
+    if sentence.chars().count() > MAX_SENTENCE_CHARS {
+        let cut: String = sentence.chars().take(MAX_SENTENCE_CHARS).collect();
+        format!("{}…", cut)
+    } else {
+        sentence.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EDITORIAL: &str = "\
+頭の位置の履歴を deque で持ちます。部分 $p$ の位置は $p-1$ 回前の頭の位置です。
+
+## 実装
+
+各クエリで先頭に追加します. 計算量は $O(N+Q)$ です。
+
+```cpp
+int main() {
+
+    return 0;
+}
+```
+
+## 補足
+
+残りの説明。さらに続く。";
+
+    #[test]
+    fn finds_techniques() {
+        assert_eq!(
+            techniques(EDITORIAL),
+            vec!["データ構造 (スタック / キュー / ヒープ)"]
+        );
+        // 大文字の略語は部分文字列に反応しない
+        assert!(techniques("update the dp table").is_empty());
+        assert_eq!(techniques("DP で解きます"), vec!["動的計画法 (DP)"]);
+    }
+
+    #[test]
+    fn key_observation_is_first_paragraph() {
+        assert_eq!(
+            key_observation(EDITORIAL).as_deref(),
+            Some("頭の位置の履歴を deque で持ちます。部分 $p$ の位置は $p-1$ 回前の頭の位置です。")
+        );
+    }
+
+    #[test]
+    fn outline_keeps_headings_and_first_sentences() {
+        assert_eq!(
+            outline(EDITORIAL).as_deref(),
+            Some(
+                "- 頭の位置の履歴を deque で持ちます。\n\
+                 ## 実装\n\
+                 - 各クエリで先頭に追加します.\n\
+                 ## 補足\n\
+                 - 残りの説明。"
+            )
+        );
+    }
+
+    #[test]
+    fn outline_does_not_split_inside_math_and_is_bounded() {
+        assert_eq!(
+            outline("答えは $1.5 \\times N$ です。以上。").as_deref(),
+            Some("- 答えは $1.5 \\times N$ です。")
+        );
+        let long = "あ".repeat(MAX_SENTENCE_CHARS + 50);
+        let bounded = outline(&long).unwrap();
+        assert_eq!(bounded.chars().count(), "- ".len() + MAX_SENTENCE_CHARS + 1);
+
+        let many = (0..30)
+            .map(|i| format!("段落 {}。", i))
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        assert_eq!(outline(&many).unwrap().lines().count(), MAX_OUTLINE_LINES);
+    }
+
+    #[test]
+    fn outline_skips_code_only_editorials() {
+        assert_eq!(outline("```\ncode\n```"), None);
+    }
+}
@@ -0,0 +1,228 @@
+//! atcoder.jp への HTTP アクセス
+//!
+//! クライアントはサーバー全体で1つだけ作り、接続を使い回す。
+//! ホストごとのトークンバケットで間隔を空け (既定では1秒に1回)、
+//! 5xx と 429 は Retry-After を守りつつ指数バックオフで再試行する。
+//! Retry-After が再試行の待ち時間の上限より長いときは、早めに再試行せずにそのエラーを返す。
+
+use crate::config::HttpConfig;
+use crate::time;
+use anyhow::Context;
+use reqwest::{Client, RequestBuilder, Response, StatusCode, header};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 共有の HTTP クライアント
+pub struct HttpClient {
+    client: Client,
+    limiter: RateLimiter,
+    max_retries: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl HttpClient {
+    pub fn new(config: &HttpConfig) -> anyhow::Result<Self> {
+        // User-Agentを設定しないと拒否されるかもなので注意
+        let client = Client::builder()
+            .user_agent("atcoder-hint-mcp/0.1.0")
+            .connect_timeout(Duration::from_secs(config.connect_timeout_secs))
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .context("Failed to build HTTP client")?;
+        Ok(Self {
+            client,
+            limiter: RateLimiter::new(config.requests_per_second, config.burst),
+            max_retries: config.max_retries,
+            initial_backoff: Duration::from_millis(config.initial_backoff_ms),
+            max_backoff: Duration::from_millis(config.max_backoff_ms),
+        })
+    }
+
+    /// GET リクエストを作る (送るのは `send`)
+    pub fn get(&self, url: &str) -> RequestBuilder {
+        self.client.get(url)
+    }
+
+    /// 間隔を守って送り、一時的なエラーなら再試行する
+    /// 再試行し尽くした 5xx / 429 はそのままレスポンスとして返す
+    pub async fn send(&self, request: RequestBuilder) -> anyhow::Result<Response> {
+        let mut attempt = 0;
+        loop {
+            let request = request
+                .try_clone()
+                .context("Request cannot be retried")?
+                .build()?;
+            let host = request.url().host_str().unwrap_or_default().to_string();
+            self.limiter.acquire(&host).await;
+
+            let result = self.client.execute(request).await;
+            let delay = match &result {
+                Ok(resp) if is_transient(resp.status()) => match retry_after(resp) {
+                    // 指定より早く再試行するとブロックされかねないので、待てないなら諦める
+                    Some(delay) if delay > self.max_backoff => None,
+                    Some(delay) => Some(delay),
+                    None => Some(self.backoff(attempt)),
+                },
+                Err(e) if e.is_timeout() || e.is_connect() => Some(self.backoff(attempt)),
+                _ => None,
+            };
+            match delay {
+                Some(delay) if attempt < self.max_retries => {
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                _ => return Ok(result?),
+            }
+        }
+    }
+
+    /// 1回目は initial_backoff、以降は倍々に延ばす
+    fn backoff(&self, attempt: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_backoff)
+    }
+}
+
+/// 再試行すべきステータス
+fn is_transient(status: StatusCode) -> bool {
+    status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+/// Retry-After (秒数か HTTP-date) を読む
+fn retry_after(resp: &Response) -> Option<Duration> {
+    let value = resp.headers().get(header::RETRY_AFTER)?.to_str().ok()?;
+    parse_retry_after(value, time::now() as i64)
+}
+
+/// Retry-After の値を、`now` (UNIX 秒) からの待ち時間にする
+fn parse_retry_after(value: &str, now: i64) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = time::parse_http_date(value)?;
+    Some(Duration::from_secs(at.saturating_sub(now).max(0) as u64))
+}
+
+/// ホストごとのトークンバケット
+struct RateLimiter {
+    /// 1秒あたりに補充するトークン数 (0 以下なら制限しない)
+    rate: f64,
+    /// 貯められるトークンの上限
+    burst: f64,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+struct Bucket {
+    /// 残りのトークン (先に予約した分だけ負になる)
+    tokens: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    fn new(rate: f64, burst: u32) -> Self {
+        Self {
+            rate,
+            burst: f64::from(burst.max(1)),
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// トークンを1つ取る。足りなければ補充されるまで待つ
+    async fn acquire(&self, host: &str) {
+        if let Some(wait) = self.reserve(host, Instant::now()) {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// `now` の時点でトークンを1つ予約し、足りなければ待つべき時間を返す
+    /// (順番待ちの分は先に差し引いておくので、同時に呼ばれても間隔は守られる)
+    fn reserve(&self, host: &str, now: Instant) -> Option<Duration> {
+        if self.rate <= 0.0 {
+            return None;
+        }
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = buckets.entry(host.to_string()).or_insert(Bucket {
+            tokens: self.burst,
+            updated: now,
+        });
+        let refill = now.duration_since(bucket.updated).as_secs_f64() * self.rate;
+        bucket.tokens = (bucket.tokens + refill).min(self.burst) - 1.0;
+        bucket.updated = now;
+        (bucket.tokens < 0.0).then(|| Duration::from_secs_f64(-bucket.tokens / self.rate))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_retry_after_seconds() {
+        assert_eq!(parse_retry_after("120", 0), Some(Duration::from_secs(120)));
+        assert_eq!(parse_retry_after(" 0 ", 0), Some(Duration::ZERO));
+        assert_eq!(parse_retry_after("soon", 0), None);
+        assert_eq!(parse_retry_after("-5", 0), None);
+    }
+
+    #[test]
+    fn parses_retry_after_http_date() {
+        // Sun, 06 Nov 1994 08:49:37 GMT = 784111777
+        let date = "Sun, 06 Nov 1994 08:49:37 GMT";
+        assert_eq!(
+            parse_retry_after(date, 784_111_777 - 90),
+            Some(Duration::from_secs(90))
+        );
+        // 過去の日時なら待たない
+        assert_eq!(
+            parse_retry_after(date, 784_111_777 + 10),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn bucket_allows_burst_then_spaces_requests() {
+        let limiter = RateLimiter::new(1.0, 2);
+        let start = Instant::now();
+        assert_eq!(limiter.reserve("atcoder.jp", start), None);
+        assert_eq!(limiter.reserve("atcoder.jp", start), None);
+        assert_eq!(
+            limiter.reserve("atcoder.jp", start),
+            Some(Duration::from_secs(1))
+        );
+        // 予約済みの分も数えるので、続けて呼ぶと待ち時間が延びる
+        assert_eq!(
+            limiter.reserve("atcoder.jp", start),
+            Some(Duration::from_secs(2))
+        );
+        // ホストごとに別のバケット
+        assert_eq!(limiter.reserve("img.atcoder.jp", start), None);
+    }
+
+    #[test]
+    fn bucket_refills_over_time_up_to_burst() {
+        let limiter = RateLimiter::new(2.0, 1);
+        let start = Instant::now();
+        assert_eq!(limiter.reserve("atcoder.jp", start), None);
+        assert_eq!(
+            limiter.reserve("atcoder.jp", start + Duration::from_millis(250)),
+            Some(Duration::from_millis(250))
+        );
+        // 長く空いても burst 以上は貯まらない
+        let later = start + Duration::from_secs(60);
+        assert_eq!(limiter.reserve("atcoder.jp", later), None);
+        assert!(limiter.reserve("atcoder.jp", later).is_some());
+    }
+
+    #[test]
+    fn zero_rate_disables_limit() {
+        let limiter = RateLimiter::new(0.0, 1);
+        let now = Instant::now();
+        for _ in 0..10 {
+            assert_eq!(limiter.reserve("atcoder.jp", now), None);
+        }
+    }
+}
@@ -0,0 +1,428 @@
+//! AtCoder の HTML を Markdown に変換する
+//!
+//! 問題文は見出し・リスト・表・`<pre>` のサンプル・`<var>` の数式が混ざっているので、
+//! テキストを平らに繋げるとモデルが制約を読み違える。構造を保ったまま Markdown にする。
+
+use scraper::{ElementRef, node::Node};
+
+/// 要素の中身を Markdown に変換する
+pub fn to_markdown(element: ElementRef) -> String {
+    let mut blocks = Vec::new();
+    render_blocks(element, &mut blocks);
+    blocks.join("\n\n")
+}
+
+/// ブロック要素として扱うタグ
+fn is_block(name: &str) -> bool {
+    matches!(
+        name,
+        "address"
+            | "article"
+            | "blockquote"
+            | "div"
+            | "dl"
+            | "dt"
+            | "dd"
+            | "footer"
+            | "h1"
+            | "h2"
+            | "h3"
+            | "h4"
+            | "h5"
+            | "h6"
+            | "header"
+            | "hr"
+            | "li"
+            | "ol"
+            | "p"
+            | "pre"
+            | "section"
+            | "table"
+            | "ul"
+    )
+}
+
+/// 出力しない要素 (スクリプトや「Copy」ボタンなど)
+fn is_skipped(element: ElementRef) -> bool {
+    let name = element.value().name();
+    if matches!(name, "script" | "style" | "button" | "noscript") {
+        return true;
+    }
+    element
+        .value()
+        .classes()
+        .any(|c| c == "btn-copy" || c == "div-btn-copy")
+}
+
+/// 中にブロック要素を含むか (AtCoder は `<span class="lang-ja">` の中に段落を置く)
+fn contains_block(element: ElementRef) -> bool {
+    element
+        .descendants()
+        .filter_map(ElementRef::wrap)
+        .any(|e| is_block(e.value().name()))
+}
+
+/// 子要素を順に見て、インラインの連続は1段落にまとめ、ブロックはそれぞれ変換する
+fn render_blocks(element: ElementRef, out: &mut Vec<String>) {
+    let mut paragraph = String::new();
+
+    for child in element.children() {
+        match child.value() {
+            Node::Text(text) => paragraph.push_str(&inline_text(text)),
+            Node::Element(_) => {
+                let Some(child) = ElementRef::wrap(child) else {
+                    continue;
+                };
+                if is_skipped(child) {
+                    continue;
+                }
+                if is_block(child.value().name()) || contains_block(child) {
+                    flush_paragraph(&mut paragraph, out);
+                    render_block(child, out);
+                } else {
+                    paragraph.push_str(&render_inline(child));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    flush_paragraph(&mut paragraph, out);
+}
+
+fn flush_paragraph(paragraph: &mut String, out: &mut Vec<String>) {
+    let text = tidy_lines(paragraph);
+    if !text.is_empty() {
+        out.push(text);
+    }
+    paragraph.clear();
+}
+
+/// 行ごとの前後の空白を落とし、空行を詰める
+fn tidy_lines(text: &str) -> String {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn render_block(element: ElementRef, out: &mut Vec<String>) {
+    let name = element.value().name();
+    match name {
+        // AtCoder の問題文のセクション見出しは h3 なので、それを ## に揃える
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+            let level = match name {
+                "h1" | "h2" | "h3" => "##",
+                "h4" => "###",
+                _ => "####",
+            };
+            let title = render_inline_children(element);
+            let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+            if !title.is_empty() {
+                out.push(format!("{} {}", level, title));
+            }
+        }
+        "pre" => out.push(render_pre(element)),
+        "ul" => push_non_empty(render_list(element, false), out),
+        "ol" => push_non_empty(render_list(element, true), out),
+        "table" => push_non_empty(render_table(element), out),
+        "hr" => out.push("---".to_string()),
+        "blockquote" => {
+            let mut inner = Vec::new();
+            render_blocks(element, &mut inner);
+            let quoted = inner
+                .join("\n\n")
+                .lines()
+                .map(|line| format!("> {}", line).trim_end().to_string())
+                .collect::<Vec<_>>()
+                .join("\n");
+            push_non_empty(quoted, out);
+        }
+        // div, section, p などは中身をそのまま展開する
+        _ => render_blocks(element, out),
+    }
+}
+
+fn push_non_empty(text: String, out: &mut Vec<String>) {
+    if !text.trim().is_empty() {
+        out.push(text);
+    }
+}
+
+/// `<pre>` はサンプル入出力などなので、空白を崩さずにコードブロックにする
+fn render_pre(element: ElementRef) -> String {
+    let text = element.text().collect::<String>();
+    let text = text.trim_matches('\n').trim_end();
+    // 中身にバッククォートの連続があってもフェンスが閉じないようにする
+    let mut fence = "```".to_string();
+    while text.contains(fence.as_str()) {
+        fence.push('`');
+    }
+    let info = code_language(element).unwrap_or_default();
+    format!("{}{}\n{}\n{}", fence, info, text, fence)
+}
+
+/// コードブロックの言語 (`<pre class="lang-cpp">` や `<code class="language-rust">`)
+fn code_language(pre: ElementRef) -> Option<String> {
+    let code = pre
+        .children()
+        .filter_map(ElementRef::wrap)
+        .find(|e| e.value().name() == "code");
+    std::iter::once(pre)
+        .chain(code)
+        .flat_map(|e| e.value().classes())
+        .find_map(|class| {
+            class
+                .strip_prefix("language-")
+                .or_else(|| class.strip_prefix("lang-"))
+        })
+        .filter(|lang| {
+            !lang.is_empty()
+                && lang
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "+#-_".contains(c))
+        })
+        .map(str::to_string)
+}
+
+fn render_list(element: ElementRef, ordered: bool) -> String {
+    let mut lines = Vec::new();
+    let items = element
+        .children()
+        .filter_map(ElementRef::wrap)
+        .filter(|e| e.value().name() == "li");
+
+    for (i, item) in items.enumerate() {
+        let marker = if ordered {
+            format!("{}. ", i + 1)
+        } else {
+            "- ".to_string()
+        };
+        let indent = " ".repeat(marker.len());
+
+        let mut blocks = Vec::new();
+        render_blocks(item, &mut blocks);
+        let body = blocks.join("\n");
+
+        for (j, line) in body.lines().enumerate() {
+            if j == 0 {
+                lines.push(format!("{}{}", marker, line));
+            } else {
+                lines.push(format!("{}{}", indent, line));
+            }
+        }
+        if body.is_empty() {
+            lines.push(marker.trim_end().to_string());
+        }
+    }
+    lines.join("\n")
+}
+
+fn render_table(element: ElementRef) -> String {
+    let rows: Vec<Vec<String>> = element
+        .descendants()
+        .filter_map(ElementRef::wrap)
+        .filter(|e| e.value().name() == "tr")
+        .map(|tr| {
+            tr.children()
+                .filter_map(ElementRef::wrap)
+                .filter(|c| matches!(c.value().name(), "th" | "td"))
+                .map(|cell| {
+                    render_inline_children(cell)
+                        .split_whitespace()
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                        .replace('|', "\\|")
+                })
+                .collect()
+        })
+        .filter(|row: &Vec<String>| !row.is_empty())
+        .collect();
+
+    let Some(columns) = rows.iter().map(Vec::len).max() else {
+        return String::new();
+    };
+
+    let format_row = |row: &[String]| {
+        let mut cells: Vec<&str> = row.iter().map(String::as_str).collect();
+        cells.resize(columns, "");
+        format!("| {} |", cells.join(" | "))
+    };
+
+    // 先頭行を見出し行として扱う (AtCoder の表はほぼ th 始まり)
+    let mut lines = vec![
+        format_row(&rows[0]),
+        format!("|{}", " --- |".repeat(columns)),
+    ];
+    lines.extend(rows[1..].iter().map(|row| format_row(row)));
+    lines.join("\n")
+}
+
+fn render_inline_children(element: ElementRef) -> String {
+    let mut text = String::new();
+    for child in element.children() {
+        match child.value() {
+            Node::Text(t) => text.push_str(&inline_text(t)),
+            Node::Element(_) => {
+                if let Some(child) = ElementRef::wrap(child)
+                    && !is_skipped(child)
+                {
+                    text.push_str(&render_inline(child));
+                }
+            }
+            _ => {}
+        }
+    }
+    text
+}
+
+fn render_inline(element: ElementRef) -> String {
+    match element.value().name() {
+        // <var> は MathJax で数式として表示される部分なので $...$ にする
+        "var" => {
+            let tex = element.text().collect::<String>();
+            format!("${}$", tex.trim())
+        }
+        "code" | "kbd" | "samp" => {
+            let code = element.text().collect::<String>();
+            format!("`{}`", code.trim())
+        }
+        "strong" | "b" => wrap_non_empty("**", render_inline_children(element)),
+        "em" | "i" => wrap_non_empty("*", render_inline_children(element)),
+        "br" => "\n".to_string(),
+        "a" => {
+            let label = render_inline_children(element);
+            match element.value().attr("href") {
+                Some(href) if !label.trim().is_empty() => format!("[{}]({})", label.trim(), href),
+                _ => label,
+            }
+        }
+        "img" => {
+            let alt = element.value().attr("alt").unwrap_or("");
+            match element.value().attr("src") {
+                Some(src) => format!("![{}]({})", alt, src),
+                None => String::new(),
+            }
+        }
+        _ => render_inline_children(element),
+    }
+}
+
+fn wrap_non_empty(mark: &str, text: String) -> String {
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        text
+    } else {
+        format!("{}{}{}", mark, trimmed, mark)
+    }
+}
+
+/// 通常のテキストノード: 空白をまとめ、MathJax の区切り `\( \)` `\[ \]` を $ に置き換える
+fn inline_text(text: &str) -> String {
+    let mut collapsed = String::with_capacity(text.len());
+    let mut last_was_space = false;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !last_was_space {
+                collapsed.push(' ');
+            }
+            last_was_space = true;
+        } else {
+            collapsed.push(c);
+            last_was_space = false;
+        }
+    }
+    collapsed
+        .replace("\\(", "$")
+        .replace("\\)", "$")
+        .replace("\\[", "$$")
+        .replace("\\]", "$$")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scraper::Html;
+
+    fn convert(html: &str) -> String {
+        let document = Html::parse_fragment(html);
+        to_markdown(document.root_element())
+    }
+
+    #[test]
+    fn converts_headings_and_math() {
+        assert_eq!(
+            convert("<h3>問題文</h3><p>長さ <var>N</var> の数列 \\(A\\) があります。</p>"),
+            "## 問題文\n\n長さ $N$ の数列 $A$ があります。"
+        );
+        assert_eq!(convert("<h4>Note</h4>"), "### Note");
+    }
+
+    #[test]
+    fn keeps_sample_whitespace() {
+        assert_eq!(convert("<pre>3\n1  2 3\n</pre>"), "```\n3\n1  2 3\n```");
+    }
+
+    #[test]
+    fn marks_code_language() {
+        assert_eq!(
+            convert(r#"<pre><code class="language-rust">fn main() {}</code></pre>"#),
+            "```rust\nfn main() {}\n```"
+        );
+        assert_eq!(
+            convert(r#"<pre class="prettyprint lang-cpp">int main() {}</pre>"#),
+            "```cpp\nint main() {}\n```"
+        );
+    }
+
+    #[test]
+    fn lengthens_fence_around_backticks() {
+        assert_eq!(convert("<pre>a```b</pre>"), "````\na```b\n````");
+    }
+
+    #[test]
+    fn converts_lists() {
+        assert_eq!(
+            convert("<ul><li><var>1 \\leq N \\leq 10^5</var></li><li>入力はすべて整数</li></ul>"),
+            "- $1 \\leq N \\leq 10^5$\n- 入力はすべて整数"
+        );
+        assert_eq!(convert("<ol><li>a</li><li>b</li></ol>"), "1. a\n2. b");
+    }
+
+    #[test]
+    fn converts_tables() {
+        assert_eq!(
+            convert("<table><tr><th>x</th><th>y|z</th></tr><tr><td>1</td></tr></table>"),
+            "| x | y\\|z |\n| --- | --- |\n| 1 |  |"
+        );
+    }
+
+    #[test]
+    fn converts_inline_markup() {
+        assert_eq!(
+            convert(
+                r#"<p><strong>注意</strong>: <code>long long</code> を使う (<a href="/x">詳細</a>)</p>"#
+            ),
+            "**注意**: `long long` を使う ([詳細](/x))"
+        );
+    }
+
+    #[test]
+    fn skips_buttons_and_scripts() {
+        assert_eq!(
+            convert(
+                r#"<div><span class="btn-copy">Copy</span><script>var x;</script><p>本文</p></div>"#
+            ),
+            "本文"
+        );
+    }
+
+    #[test]
+    fn unwraps_blocks_inside_inline_elements() {
+        assert_eq!(
+            convert(r#"<span class="lang-ja"><p>一</p><p>二</p></span>"#),
+            "一\n\n二"
+        );
+    }
+}
@@ -0,0 +1,396 @@
+//! 問題ページを構造化したモデル
+//!
+//! テストランナーやヒント用プロンプトが、サンプルや制限をテキストから読み直さずに済むように、
+//! 問題ページを `Problem` に分解する。MCP の structuredContent としてもそのまま返す。
+
+use crate::atcoder::{Lang, select_lang};
+use crate::markdown;
+use scraper::{ElementRef, Html, Selector};
+
+tool_output! {
+    /// サンプル入出力1組
+    pub struct Sample {
+        /// サンプル番号 (入力例 N の N)
+        index: u32,
+        /// 入力
+        input: String,
+        /// 期待される出力
+        output: String,
+    }
+}
+
+tool_output! {
+    /// 問題ページの内容
+    pub struct Problem {
+        /// コンテストID
+        contest_id: String,
+        /// 問題ID
+        problem_id: String,
+        /// 問題ページの URL
+        url: String,
+        /// 問題名 (例: Tomorrow)
+        title: String,
+        /// 問題の記号 (例: A)
+        task_letter: Option<String>,
+        /// 実行時間制限 (ミリ秒)
+        time_limit_ms: Option<u64>,
+        /// メモリ制限 (MB)
+        memory_limit_mb: Option<u64>,
+        /// 配点
+        score: Option<u64>,
+        /// 許容誤差 (「絶対誤差または相対誤差が 10^{-6} 以下なら正解」の 10^{-6})
+        tolerance: Option<f64>,
+        /// 問題文 (Markdown)
+        statement: Option<String>,
+        /// 制約 (Markdown)
+        constraints: Option<String>,
+        /// 入力形式 (Markdown)
+        input_format: Option<String>,
+        /// 出力形式 (Markdown)
+        output_format: Option<String>,
+        /// サンプル入出力
+        samples: Vec<Sample>,
+    }
+}
+
+/// 取得した問題ページ: 構造化したものと、LLM にそのまま見せる Markdown
+pub struct ProblemPage {
+    pub problem: Problem,
+    pub markdown: String,
+}
+
+/// 問題文の中のセクションの種類 (h3 の見出しで判断する)
+enum SectionKind {
+    Statement,
+    Constraints,
+    Input,
+    Output,
+    SampleInput(u32),
+    SampleOutput(u32),
+    Other,
+}
+
+fn classify(title: &str) -> SectionKind {
+    let sample_index = |rest: &str| rest.trim().parse::<u32>().ok();
+    if let Some(n) = ["入力例", "Sample Input"]
+        .iter()
+        .find_map(|p| title.strip_prefix(p).and_then(sample_index))
+    {
+        return SectionKind::SampleInput(n);
+    }
+    if let Some(n) = ["出力例", "Sample Output"]
+        .iter()
+        .find_map(|p| title.strip_prefix(p).and_then(sample_index))
+    {
+        return SectionKind::SampleOutput(n);
+    }
+    match title {
+        "問題文" | "Problem Statement" | "Problem" => SectionKind::Statement,
+        "制約" | "Constraints" => SectionKind::Constraints,
+        "入力" | "Input" => SectionKind::Input,
+        "出力" | "Output" => SectionKind::Output,
+        _ => SectionKind::Other,
+    }
+}
+
+/// 問題ページの HTML を解析する
+pub fn parse_problem_page(
+    html: &str,
+    contest_id: &str,
+    problem_id: &str,
+    url: &str,
+    lang: Lang,
+) -> anyhow::Result<ProblemPage> {
+    let document = Html::parse_document(html);
+
+    // 問題文のセクションを取得(AtCoderのHTML構造に依存)
+    // #task-statement というIDの中に問題文がある。らしい。
+    let selector = Selector::parse("#task-statement").unwrap();
+    let Some(statement) = document.select(&selector).next() else {
+        anyhow::bail!("Could not find problem statement in HTML.")
+    };
+
+    // 見出し・サンプル・表・数式を保ったまま Markdown にする
+    let markdown = markdown::to_markdown(select_lang(statement, lang));
+
+    // 構造化は1言語分だけから行う (both のときに同じサンプルが2回並ばないように)
+    let structure_lang = if lang == Lang::Both { Lang::Ja } else { lang };
+    let body = select_lang(statement, structure_lang);
+
+    let (task_letter, title) = parse_title(&document);
+    let (time_limit_ms, memory_limit_mb) = parse_limits(&document);
+
+    let mut problem = Problem {
+        contest_id: contest_id.to_string(),
+        problem_id: problem_id.to_string(),
+        url: url.to_string(),
+        title,
+        task_letter,
+        time_limit_ms,
+        memory_limit_mb,
+        score: parse_score(statement),
+        tolerance: None,
+        statement: None,
+        constraints: None,
+        input_format: None,
+        output_format: None,
+        samples: Vec::new(),
+    };
+
+    let mut inputs = Vec::new();
+    let mut outputs = Vec::new();
+    let section_selector = Selector::parse("section").unwrap();
+    let h3_selector = Selector::parse("h3").unwrap();
+    let pre_selector = Selector::parse("pre").unwrap();
+
+    for section in body.select(&section_selector) {
+        let Some(heading) = section.select(&h3_selector).next() else {
+            continue;
+        };
+        let title = markdown::to_markdown(heading);
+        let title = title.trim_start_matches('#').trim();
+
+        match classify(title) {
+            SectionKind::Statement => problem.statement = Some(section_body(section)),
+            SectionKind::Constraints => problem.constraints = Some(section_body(section)),
+            SectionKind::Input => problem.input_format = Some(section_body(section)),
+            SectionKind::Output => problem.output_format = Some(section_body(section)),
+            SectionKind::SampleInput(n) => {
+                if let Some(pre) = section.select(&pre_selector).next() {
+                    inputs.push((n, sample_text(pre)));
+                }
+            }
+            SectionKind::SampleOutput(n) => {
+                if let Some(pre) = section.select(&pre_selector).next() {
+                    outputs.push((n, sample_text(pre)));
+                }
+            }
+            SectionKind::Other => {}
+        }
+    }
+
+    // 誤差の許容は出力の節に書かれることが多いが、問題文の末尾にあることもある
+    problem.tolerance = [&problem.output_format, &problem.statement]
+        .into_iter()
+        .flatten()
+        .find_map(|text| parse_tolerance(text));
+
+    for (index, input) in inputs {
+        if let Some((_, output)) = outputs.iter().find(|(n, _)| *n == index) {
+            problem.samples.push(Sample {
+                index,
+                input,
+                output: output.clone(),
+            });
+        }
+    }
+
+    Ok(ProblemPage { problem, markdown })
+}
+
+/// セクションの中身を Markdown にする (先頭の見出しは除く)
+fn section_body(section: ElementRef) -> String {
+    let text = markdown::to_markdown(section);
+    match text.split_once("\n\n") {
+        Some((first, rest)) if first.starts_with('#') => rest.to_string(),
+        _ if text.starts_with('#') => String::new(),
+        _ => text,
+    }
+}
+
+/// サンプルの `<pre>` の中身。前後の空行を落とし、末尾の改行を1つにそろえる
+fn sample_text(pre: ElementRef) -> String {
+    let text = pre.text().collect::<String>();
+    let text = text.trim_start_matches(['\n', '\r']).trim_end();
+    if text.is_empty() {
+        String::new()
+    } else {
+        format!("{}\n", text)
+    }
+}
+
+/// `span.h2` の「A - Tomorrow」から記号と問題名を取り出す
+fn parse_title(document: &Html) -> (Option<String>, String) {
+    let selector = Selector::parse("span.h2").unwrap();
+    let Some(heading) = document.select(&selector).next() else {
+        return (None, String::new());
+    };
+    // 「解説」ボタンなどの子要素は除いて、直下のテキストだけを使う
+    let text = heading
+        .children()
+        .filter_map(|c| c.value().as_text().map(|t| t.to_string()))
+        .collect::<String>();
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    match text.split_once(" - ") {
+        Some((letter, title)) => (Some(letter.to_string()), title.to_string()),
+        None => (None, text),
+    }
+}
+
+/// 「実行時間制限: 2 sec / メモリ制限: 1024 MB」(英語版は Time Limit / Memory Limit) を読む
+fn parse_limits(document: &Html) -> (Option<u64>, Option<u64>) {
+    let selector = Selector::parse("#main-container p").unwrap();
+    for p in document.select(&selector) {
+        let text = p.text().collect::<String>();
+        let (Some(time), Some(memory)) = (
+            text.split_once('/').map(|(t, _)| t),
+            text.split_once('/').map(|(_, m)| m),
+        ) else {
+            continue;
+        };
+        let time_ms = parse_time_limit(time);
+        let memory_mb = parse_memory_limit(memory);
+        if time_ms.is_some() || memory_mb.is_some() {
+            return (time_ms, memory_mb);
+        }
+    }
+    (None, None)
+}
+
+/// 「2 sec」「2000 msec」を読んでミリ秒にする
+pub fn parse_time_limit(text: &str) -> Option<u64> {
+    number_before(text, "msec")
+        .or_else(|| number_before(text, "ms"))
+        .or_else(|| number_before(text, "sec").map(|sec| sec * 1000.0))
+        .map(|ms| ms.round() as u64)
+}
+
+/// 「1024 MB」「256 MiB」を読んで MB にする
+pub fn parse_memory_limit(text: &str) -> Option<u64> {
+    number_before(text, "MiB")
+        .or_else(|| number_before(text, "MB"))
+        .or_else(|| number_before(text, "KiB").map(|kb| kb / 1024.0))
+        .or_else(|| number_before(text, "KB").map(|kb| kb / 1024.0))
+        .map(|mb| mb.round() as u64)
+}
+
+/// 「配点 : 100 点」/「Score : 100 points」を読む
+fn parse_score(statement: ElementRef) -> Option<u64> {
+    let selector = Selector::parse("p").unwrap();
+    statement.select(&selector).find_map(|p| {
+        let text = p.text().collect::<String>();
+        let (label, rest) = text.split_once(':')?;
+        let label = label.trim();
+        if label != "配点" && label != "Score" {
+            return None;
+        }
+        rest.split_whitespace().next()?.parse().ok()
+    })
+}
+
+/// 「誤差が $10^{-6}$ 以下」「error ... at most $10^{-6}$」のような文から許容誤差を読む
+fn parse_tolerance(text: &str) -> Option<f64> {
+    text.split("\n\n")
+        .filter(|p| p.contains("誤差") || p.to_ascii_lowercase().contains("error"))
+        .find_map(|p| {
+            // 数式の書き方の揺れ (10^{-6}, 10^{ - 6 }, 10^-6, −) をならしてから探す
+            let compact: String = p
+                .chars()
+                .filter(|c| !c.is_whitespace() && !matches!(c, '{' | '}' | '$'))
+                .map(|c| if c == '−' { '-' } else { c })
+                .collect();
+            let (before, after) = compact.split_once("10^-")?;
+            let digits: String = after.chars().take_while(char::is_ascii_digit).collect();
+            let exponent: i32 = digits.parse().ok()?;
+            // 「2 \times 10^{-6}」のような係数付きの書き方
+            let coefficient = before
+                .strip_suffix("\\times")
+                .and_then(|b| {
+                    let start = b
+                        .rfind(|c: char| !(c.is_ascii_digit() || c == '.'))
+                        .map_or(0, |i| i + b[i..].chars().next().map_or(1, char::len_utf8));
+                    b[start..].parse::<f64>().ok()
+                })
+                .unwrap_or(1.0);
+            Some(coefficient * 10f64.powi(-exponent))
+        })
+}
+
+/// `unit` の直前にある数値を読む (「2 sec」「1024 MB」など)
+fn number_before(text: &str, unit: &str) -> Option<f64> {
+    let end = text.find(unit)?;
+    let before = text[..end].trim_end();
+    let start = before
+        .rfind(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .map(|i| i + before[i..].chars().next().map_or(1, char::len_utf8))
+        .unwrap_or(0);
+    before[start..].parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score_of(html: &str) -> Option<u64> {
+        let document = Html::parse_fragment(html);
+        parse_score(document.root_element())
+    }
+
+    #[test]
+    fn parses_time_limit() {
+        assert_eq!(parse_time_limit("実行時間制限: 2 sec "), Some(2000));
+        assert_eq!(parse_time_limit("Time Limit: 2.5 sec"), Some(2500));
+        assert_eq!(parse_time_limit("Time Limit: 5000 msec"), Some(5000));
+        assert_eq!(parse_time_limit("Time Limit: 300 ms"), Some(300));
+        assert_eq!(parse_time_limit("no limit here"), None);
+    }
+
+    #[test]
+    fn parses_memory_limit() {
+        assert_eq!(parse_memory_limit(" メモリ制限: 1024 MB"), Some(1024));
+        assert_eq!(parse_memory_limit("Memory Limit: 256 MiB"), Some(256));
+        assert_eq!(parse_memory_limit("Memory Limit: 262144 KB"), Some(256));
+        assert_eq!(parse_memory_limit("Memory Limit: unknown"), None);
+    }
+
+    #[test]
+    fn parses_limits_line() {
+        let document = Html::parse_document(
+            r#"<div id="main-container"><p>実行時間制限: 2 sec / メモリ制限: 1024 MB</p></div>"#,
+        );
+        assert_eq!(parse_limits(&document), (Some(2000), Some(1024)));
+    }
+
+    #[test]
+    fn parses_score() {
+        assert_eq!(score_of("<p>配点 : <var>300</var> 点</p>"), Some(300));
+        assert_eq!(score_of("<p>Score : <var>500</var> points</p>"), Some(500));
+        assert_eq!(
+            score_of("<p>問題文</p><p>配点 : <var>100</var> 点</p>"),
+            Some(100)
+        );
+        assert_eq!(score_of("<p>制約 : なし</p>"), None);
+    }
+
+    #[test]
+    fn parses_tolerance() {
+        assert_eq!(
+            parse_tolerance(
+                "真の値との絶対誤差または相対誤差が $10^{-6}$ 以下であれば正解とみなされる。"
+            ),
+            Some(1e-6)
+        );
+        assert_eq!(
+            parse_tolerance(
+                "Your output is considered correct if the absolute or relative error is at most $10^{ - 9 }$."
+            ),
+            Some(1e-9)
+        );
+        assert_eq!(
+            parse_tolerance("誤差が $2 \\times 10^{−5}$ 以下なら正解"),
+            Some(2.0 * 1e-5)
+        );
+    }
+
+    #[test]
+    fn ignores_powers_outside_error_paragraphs() {
+        assert_eq!(
+            parse_tolerance("答えを $10^{-9}$ で割った余りを出力せよ。"),
+            None
+        );
+        assert_eq!(
+            parse_tolerance("$1 \\leq N \\leq 10^5$\n\n誤差は許されない。"),
+            None
+        );
+    }
+}
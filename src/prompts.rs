@@ -0,0 +1,423 @@
+//! ヒント用の MCP プロンプト
+//!
+//! 問題文 (と必要なら解説) を取得してメッセージに埋め込むので、
+//! 誰のエディタから使っても同じ言い回しでヒントを頼める。
+
+use crate::atcoder::AtCoder;
+use crate::config::Config;
+use crate::reference;
+use crate::rpc::{INTERNAL_ERROR, RpcError};
+use serde_json::{Map, Value, json};
+
+/// プロンプトの引数定義
+struct PromptArgument {
+    name: &'static str,
+    description: &'static str,
+    required: bool,
+}
+
+// 問題の指定はツールの ProblemArgs と同じ (problem か contest_id + problem_id のどちらか)
+const PROBLEM: PromptArgument = PromptArgument {
+    name: "problem",
+    description: "問題 (例: \"ABC335 C\", \"abc335c\", \"abc335_c\", 問題ページの URL)。contest_id と problem_id の代わりに使える",
+    required: false,
+};
+
+const CONTEST_ID: PromptArgument = PromptArgument {
+    name: "contest_id",
+    description: "コンテストID (例: abc335)。problem を使わないときは必須",
+    required: false,
+};
+
+const PROBLEM_ID: PromptArgument = PromptArgument {
+    name: "problem_id",
+    description: "問題ID (例: abc335_a) または問題の記号 (例: A)。problem を使わないときは必須",
+    required: false,
+};
+
+/// 組み込みのプロンプトテンプレート
+struct PromptTemplate {
+    name: &'static str,
+    title: &'static str,
+    description: &'static str,
+    arguments: &'static [PromptArgument],
+    /// 解説も取得して埋め込むかどうか
+    with_editorial: bool,
+    /// 依頼文を組み立てる (問題IDは解決済み、引数は検証済み)
+    instruction: fn(&str, &Map<String, Value>) -> String,
+}
+
+const PROMPTS: &[PromptTemplate] = &[
+    PromptTemplate {
+        name: "small_hint",
+        title: "小さなヒント",
+        description: "問題について、ネタバレにならない小さなヒントを1つだけもらいます。",
+        arguments: &[PROBLEM, CONTEST_ID, PROBLEM_ID],
+        with_editorial: false,
+        instruction: |problem_id, _| {
+            format!(
+                "AtCoder の問題 {} について、ごく小さなヒントを1つだけください。\n\
+                 解法やアルゴリズムの名前、コードは書かないでください。\n\
+                 考え始めるきっかけになる一言か、問いかけの形にしてください。",
+                problem_id
+            )
+        },
+    },
+    PromptTemplate {
+        name: "review_solution",
+        title: "解答のレビュー",
+        description: "自分の解答コードをレビューしてもらいます。修正版のコードは書かず、問題点と方針を指摘します。",
+        arguments: &[
+            PROBLEM,
+            CONTEST_ID,
+            PROBLEM_ID,
+            PromptArgument {
+                name: "code",
+                description: "レビューしてほしい解答コード",
+                required: true,
+            },
+            PromptArgument {
+                name: "language",
+                description: "使用言語 (例: C++, Rust, Python)",
+                required: false,
+            },
+        ],
+        with_editorial: true,
+        instruction: |problem_id, args| {
+            let language = args.get("language").and_then(Value::as_str).unwrap_or("");
+            format!(
+                "AtCoder の問題 {} に対する私の解答をレビューしてください。\n\
+                 - 正しさ (コーナーケースを含む)\n\
+                 - 計算量が制約に収まるか\n\
+                 - バグやオーバーフローの可能性\n\
+                 を確認し、問題点と直す方針を指摘してください。修正版のコード全体は書かないでください。\n\
+                 解説は参考用です。解説の内容をそのまま書き写さないでください。\n\n\
+                 ## 私の解答\n```{}\n{}\n```",
+                problem_id,
+                language.to_lowercase(),
+                arg(args, "code")
+            )
+        },
+    },
+    PromptTemplate {
+        name: "explain_editorial",
+        title: "解説の説明 (コードなし)",
+        description: "解説の考え方を、コードを示さずに説明してもらいます。",
+        arguments: &[PROBLEM, CONTEST_ID, PROBLEM_ID],
+        with_editorial: true,
+        instruction: |problem_id, _| {
+            format!(
+                "AtCoder の問題 {} の解説を、考え方が分かるように説明してください。\n\
+                 なぜその方針で解けるのか、鍵になる観察は何かを中心にしてください。\n\
+                 コードや疑似コードは書かないでください。",
+                problem_id
+            )
+        },
+    },
+];
+
+/// 検証済みの引数から文字列を取り出す
+fn arg<'a>(args: &'a Map<String, Value>, name: &str) -> &'a str {
+    args.get(name).and_then(Value::as_str).unwrap_or("")
+}
+
+/// 省略可能な引数 (空文字列は指定なしとみなす)
+fn optional_arg<'a>(args: &'a Map<String, Value>, name: &str) -> Option<&'a str> {
+    Some(arg(args, name)).filter(|s| !s.is_empty())
+}
+
+/// prompts/list の結果
+pub fn list() -> Value {
+    let prompts: Vec<Value> = PROMPTS
+        .iter()
+        .map(|p| {
+            let arguments: Vec<Value> = p
+                .arguments
+                .iter()
+                .map(|a| {
+                    json!({
+                        "name": a.name,
+                        "description": a.description,
+                        "required": a.required
+                    })
+                })
+                .collect();
+            json!({
+                "name": p.name,
+                "title": p.title,
+                "description": p.description,
+                "arguments": arguments
+            })
+        })
+        .collect();
+    json!({ "prompts": prompts })
+}
+
+/// prompts/get の処理
+pub async fn get(
+    params: Option<Value>,
+    config: &Config,
+    atcoder: &AtCoder,
+) -> Result<Value, RpcError> {
+    let params = params.ok_or_else(|| RpcError::invalid_params("Missing params"))?;
+    let name = params
+        .get("name")
+        .and_then(Value::as_str)
+        .ok_or_else(|| RpcError::invalid_params("Missing prompt name"))?;
+    let template = PROMPTS
+        .iter()
+        .find(|p| p.name == name)
+        .ok_or_else(|| RpcError::invalid_params(format!("Unknown prompt: {}", name)))?;
+
+    let args = params
+        .get("arguments")
+        .and_then(Value::as_object)
+        .cloned()
+        .unwrap_or_default();
+    for a in template.arguments.iter().filter(|a| a.required) {
+        if arg(&args, a.name).is_empty() {
+            return Err(RpcError {
+                data: Some(json!({ "field": a.name, "reason": "missing required argument" })),
+                ..RpcError::invalid_params(format!("Missing required argument: {}", a.name))
+            });
+        }
+    }
+
+    let problem = reference::from_args(
+        optional_arg(&args, "problem"),
+        optional_arg(&args, "contest_id"),
+        optional_arg(&args, "problem_id"),
+    )
+    .map_err(|e| RpcError {
+        data: Some(json!({ "field": "problem", "reason": e.to_string() })),
+        ..RpcError::invalid_params(e.to_string())
+    })?;
+    let contest_id = problem.contest_id.as_str();
+    let fetch_failed = |e: anyhow::Error| {
+        RpcError::new(
+            INTERNAL_ERROR,
+            format!("Failed to fetch {}: {}", problem.problem_id, e),
+        )
+    };
+    // 記号 (例: C) で指定されても、依頼文には問題IDを書く
+    let problem_id = atcoder
+        .resolve_problem_id(contest_id, &problem.problem_id)
+        .await
+        .map_err(fetch_failed)?;
+    let problem_id = problem_id.as_str();
+
+    let (statement, editorial) = if template.with_editorial {
+        // 開催中のコンテストの解説は埋め込まない (get_hint と同じ確認)
+        atcoder
+            .ensure_contest_finished(contest_id)
+            .await
+            .map_err(|e| RpcError::new(INTERNAL_ERROR, e.to_string()))?;
+        let (statement, editorial) = tokio::join!(
+            atcoder.fetch_problem(contest_id, problem_id, config.default_lang),
+            atcoder.fetch_official_editorial(contest_id, problem_id, config.default_lang)
+        );
+        (
+            statement.map_err(fetch_failed)?,
+            Some(editorial.map_err(fetch_failed)?),
+        )
+    } else {
+        let statement = atcoder
+            .fetch_problem(contest_id, problem_id, config.default_lang)
+            .await;
+        (statement.map_err(fetch_failed)?, None)
+    };
+
+    let mut messages = vec![user_message(format!("## 問題文\n{}", statement.markdown))];
+    if let Some(editorial) = editorial {
+        messages.push(user_message(format!("## 解説\n{}", editorial)));
+    }
+    messages.push(user_message((template.instruction)(problem_id, &args)));
+
+    Ok(json!({
+        "description": format!("{} ({})", template.title, problem_id),
+        "messages": messages
+    }))
+}
+
+fn user_message(text: String) -> Value {
+    json!({
+        "role": "user",
+        "content": { "type": "text", "text": text }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::atcoder::tests::{PAGES, offline, running_contest_page};
+    use crate::rpc::INVALID_PARAMS;
+
+    async fn get_prompt(
+        atcoder: &AtCoder,
+        name: &str,
+        arguments: Value,
+    ) -> Result<Value, RpcError> {
+        get(
+            Some(json!({ "name": name, "arguments": arguments })),
+            &Config::default(),
+            atcoder,
+        )
+        .await
+    }
+
+    fn texts(result: &Value) -> Vec<&str> {
+        result["messages"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| m["content"]["text"].as_str().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn lists_prompts_with_arguments() {
+        let list = list();
+        let prompts = list["prompts"].as_array().unwrap();
+        let names: Vec<&str> = prompts
+            .iter()
+            .map(|p| p["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            ["small_hint", "review_solution", "explain_editorial"]
+        );
+
+        let review = &prompts[1]["arguments"];
+        let required: Vec<(&str, bool)> = review
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|a| {
+                (
+                    a["name"].as_str().unwrap(),
+                    a["required"].as_bool().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            required,
+            [
+                ("problem", false),
+                ("contest_id", false),
+                ("problem_id", false),
+                ("code", true),
+                ("language", false),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_unknown_prompt_and_missing_arguments() {
+        let atcoder = offline("prompts-invalid", &[]).await;
+        let error = get(None, &Config::default(), &atcoder).await.unwrap_err();
+        assert_eq!(error.code, INVALID_PARAMS);
+        let error = get_prompt(&atcoder, "big_hint", json!({}))
+            .await
+            .unwrap_err();
+        assert_eq!(error.message, "Unknown prompt: big_hint");
+
+        for code in [json!(null), json!("")] {
+            let error = get_prompt(
+                &atcoder,
+                "review_solution",
+                json!({ "problem": "abc335_c", "code": code }),
+            )
+            .await
+            .unwrap_err();
+            assert_eq!(error.code, INVALID_PARAMS);
+            assert_eq!(error.data.unwrap()["field"], "code");
+        }
+
+        // 問題の指定が無い・食い違う
+        for arguments in [
+            json!({}),
+            json!({ "contest_id": "abc335" }),
+            json!({ "problem": "ABC335 C", "contest_id": "abc336" }),
+        ] {
+            let error = get_prompt(&atcoder, "small_hint", arguments.clone())
+                .await
+                .unwrap_err();
+            assert_eq!(error.code, INVALID_PARAMS, "{}", arguments);
+            assert_eq!(error.data.unwrap()["field"], "problem");
+        }
+    }
+
+    #[tokio::test]
+    async fn resolves_problem_argument() {
+        let atcoder = offline("prompts-problem", PAGES).await;
+        for arguments in [
+            json!({ "problem": "ABC335 C" }),
+            json!({ "problem": "https://atcoder.jp/contests/abc335/tasks/abc335_c" }),
+            json!({ "contest_id": "ABC335", "problem_id": "C" }),
+            json!({ "problem": "", "contest_id": "abc335", "problem_id": "abc335_c" }),
+        ] {
+            let result = get_prompt(&atcoder, "small_hint", arguments.clone())
+                .await
+                .unwrap();
+            assert_eq!(
+                result["description"], "小さなヒント (abc335_c)",
+                "{}",
+                arguments
+            );
+            let texts = texts(&result);
+            assert_eq!(texts.len(), 2);
+            assert!(texts[0].contains("竜を動かします。"));
+            assert!(texts[1].starts_with("AtCoder の問題 abc335_c について"));
+        }
+        let _ = std::fs::remove_dir_all(atcoder.cache().dir().unwrap());
+    }
+
+    #[tokio::test]
+    async fn embeds_editorial_of_finished_contest() {
+        let atcoder = offline("prompts-editorial", PAGES).await;
+        let result = get_prompt(
+            &atcoder,
+            "review_solution",
+            json!({ "problem": "abc335c", "code": "int main() {}", "language": "C++" }),
+        )
+        .await
+        .unwrap();
+        let texts = texts(&result);
+        assert_eq!(texts.len(), 3);
+        assert!(texts[1].starts_with("## 解説\n# C - Loong Tracking 解説"));
+        assert!(texts[2].contains("```c++\nint main() {}\n```"));
+        let _ = std::fs::remove_dir_all(atcoder.cache().dir().unwrap());
+    }
+
+    #[tokio::test]
+    async fn withholds_editorial_of_running_contest() {
+        let running = running_contest_page("ABC 998");
+        let atcoder = offline(
+            "prompts-running",
+            &[
+                ("https://atcoder.jp/contests/abc998", &running),
+                (
+                    "https://atcoder.jp/contests/abc998/tasks",
+                    r#"<div id="main-container"><table><tbody>
+                    <tr><td><a href="/contests/abc998/tasks/abc998_a">A</a></td><td>Running</td></tr>
+                    </tbody></table></div>"#,
+                ),
+            ],
+        )
+        .await;
+        let error = get_prompt(
+            &atcoder,
+            "explain_editorial",
+            json!({ "problem": "ABC998 A" }),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error.code, INTERNAL_ERROR);
+        assert!(
+            error.message.starts_with("ABC 998 is running until"),
+            "{}",
+            error.message
+        );
+        let _ = std::fs::remove_dir_all(atcoder.cache().dir().unwrap());
+    }
+}
@@ -0,0 +1,106 @@
+//! MCP のプロトコルバージョンとバージョンごとの機能差
+
+/// このサーバーが話せる MCP のバージョン (古い順)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ProtocolVersion {
+    V2024_11_05,
+    V2025_03_26,
+    V2025_06_18,
+    V2025_11_25,
+}
+
+impl ProtocolVersion {
+    pub const ALL: [ProtocolVersion; 4] = [
+        ProtocolVersion::V2024_11_05,
+        ProtocolVersion::V2025_03_26,
+        ProtocolVersion::V2025_06_18,
+        ProtocolVersion::V2025_11_25,
+    ];
+
+    pub const LATEST: ProtocolVersion = ProtocolVersion::V2025_11_25;
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ProtocolVersion::V2024_11_05 => "2024-11-05",
+            ProtocolVersion::V2025_03_26 => "2025-03-26",
+            ProtocolVersion::V2025_06_18 => "2025-06-18",
+            ProtocolVersion::V2025_11_25 => "2025-11-25",
+        }
+    }
+
+    /// クライアントが要求したバージョンから、実際に使うバージョンを決める
+    ///
+    /// バージョン名は日付なので文字列比較で新旧が分かる。
+    /// 要求以下で一番新しいものを選び、それも無ければ (要求が古すぎる) 最新版を返す。
+    /// 最新版を返された場合に続行するかどうかはクライアント側が決める (MCP の仕様)
+    pub fn negotiate(requested: &str) -> Self {
+        Self::ALL
+            .into_iter()
+            .rev()
+            .find(|v| v.as_str() <= requested)
+            .unwrap_or(Self::LATEST)
+    }
+
+    /// ツールの annotations (readOnlyHint など) は 2025-03-26 から
+    pub fn supports_tool_annotations(self) -> bool {
+        self >= ProtocolVersion::V2025_03_26
+    }
+
+    /// notifications/progress の message は 2025-03-26 から
+    pub fn supports_progress_message(self) -> bool {
+        self >= ProtocolVersion::V2025_03_26
+    }
+
+    /// 構造化出力 (outputSchema / structuredContent) は 2025-06-18 から
+    pub fn supports_structured_output(self) -> bool {
+        self >= ProtocolVersion::V2025_06_18
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn negotiates_requested_version() {
+        for version in ProtocolVersion::ALL {
+            assert_eq!(ProtocolVersion::negotiate(version.as_str()), version);
+        }
+    }
+
+    #[test]
+    fn negotiates_newest_version_not_after_request() {
+        assert_eq!(
+            ProtocolVersion::negotiate("2025-05-01"),
+            ProtocolVersion::V2025_03_26
+        );
+        assert_eq!(
+            ProtocolVersion::negotiate("2099-01-01"),
+            ProtocolVersion::LATEST
+        );
+    }
+
+    #[test]
+    fn falls_back_to_latest_for_old_or_unknown_versions() {
+        assert_eq!(
+            ProtocolVersion::negotiate("2024-01-01"),
+            ProtocolVersion::LATEST
+        );
+        assert_eq!(ProtocolVersion::negotiate(""), ProtocolVersion::LATEST);
+    }
+
+    #[test]
+    fn features_follow_version() {
+        let old = ProtocolVersion::V2024_11_05;
+        assert!(!old.supports_tool_annotations());
+        assert!(!old.supports_progress_message());
+        assert!(!old.supports_structured_output());
+
+        let middle = ProtocolVersion::V2025_03_26;
+        assert!(middle.supports_tool_annotations());
+        assert!(middle.supports_progress_message());
+        assert!(!middle.supports_structured_output());
+
+        assert!(ProtocolVersion::V2025_06_18.supports_structured_output());
+    }
+}
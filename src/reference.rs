@@ -0,0 +1,399 @@
+//! 問題の指定の仕方をならす
+//!
+//! ユーザーは "ABC335 C"、"abc335c"、"abc335_c"、問題ページの URL (`?lang=en` 付きも) など
+//! いろいろな形で問題を指定する。ここではそれをコンテストIDと問題ID (または記号) に分けるだけで、
+//! 本当にその問題があるかは `AtCoder::resolve_problem_id` が問題一覧と照らし合わせて確かめる。
+
+/// 問題の指定を分解したもの
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reference {
+    /// コンテストID (例: abc335)
+    pub contest_id: Option<String>,
+    /// 問題ID (例: abc335_c) または記号 (例: C)
+    pub task: Option<String>,
+    /// コンテストIDを問題IDから推測しただけか ("arc058_a" は abc042 の問題のこともある)
+    pub contest_guessed: bool,
+}
+
+/// 問題が決まったもの (問題IDはまだ記号のこともある)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProblemRef {
+    pub contest_id: String,
+    pub problem_id: String,
+}
+
+const EXAMPLES: &str = "e.g. \"ABC335 C\", \"abc335c\", \"abc335_c\" or https://atcoder.jp/contests/abc335/tasks/abc335_c";
+
+/// 自由な形式の問題の指定を分解する
+pub fn parse(text: &str) -> anyhow::Result<Reference> {
+    let text = text.trim();
+    if text.is_empty() {
+        anyhow::bail!("Empty problem reference ({})", EXAMPLES);
+    }
+    if let Some(reference) = parse_url(text) {
+        return reference;
+    }
+
+    // "ABC335 C" / "abc335/c" のように区切られている
+    let parts: Vec<&str> = text
+        .split(|c: char| c.is_whitespace() || c == '/')
+        .filter(|s| !s.is_empty())
+        .collect();
+    let reference = match parts.as_slice() {
+        [single] => parse_single(single),
+        [contest, task] => Reference {
+            contest_id: Some(normalize_id(contest)),
+            task: Some(task_part(contest, task)),
+            contest_guessed: false,
+        },
+        _ => anyhow::bail!(
+            "Could not understand problem reference {:?} ({})",
+            text,
+            EXAMPLES
+        ),
+    };
+    Ok(reference)
+}
+
+/// atcoder.jp の URL (`/contests/{contest_id}` と `/tasks/{problem_id}` を拾う)
+fn parse_url(text: &str) -> Option<anyhow::Result<Reference>> {
+    let rest = ["https://", "http://", "//"]
+        .iter()
+        .find_map(|scheme| text.strip_prefix(scheme))
+        .unwrap_or(text);
+    let Some(path) = rest.strip_prefix("atcoder.jp") else {
+        return text
+            .contains("://")
+            .then(|| Err(anyhow::anyhow!("Not an AtCoder URL: {}", text)));
+    };
+    let path = path.split(['?', '#']).next().unwrap_or("");
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    let reference = match segments.as_slice() {
+        ["contests", contest, "tasks", task, ..] => Reference {
+            contest_id: Some(contest.to_string()),
+            task: Some(task.to_string()),
+            contest_guessed: false,
+        },
+        ["contests", contest, ..] => Reference {
+            contest_id: Some(contest.to_string()),
+            task: None,
+            contest_guessed: false,
+        },
+        _ => {
+            return Some(Err(anyhow::anyhow!(
+                "Not an AtCoder contest or task URL: {}",
+                text
+            )));
+        }
+    };
+    Some(Ok(reference))
+}
+
+/// 区切りのない1語: "abc335_c" / "abc335c" / "abc335" / "C"
+fn parse_single(text: &str) -> Reference {
+    let id = normalize_id(text);
+    // 問題ID: コンテストIDに近いものは最後の _ より前 (正しいかは問題一覧で確かめる)
+    if let Some((contest, _)) = id.rsplit_once('_') {
+        return Reference {
+            contest_id: Some(contest.to_string()),
+            task: Some(id),
+            contest_guessed: true,
+        };
+    }
+    if crate::contest::looks_like_letter(text) {
+        return Reference {
+            contest_id: None,
+            task: Some(text.to_string()),
+            contest_guessed: false,
+        };
+    }
+    // "abc335c" のように英字+数字のコンテストIDの後ろに記号が付いている
+    let digits_end = id.rfind(|c: char| c.is_ascii_digit()).map(|i| i + 1);
+    if let Some(end) = digits_end
+        && id[..end].starts_with(|c: char| c.is_ascii_alphabetic())
+        && crate::contest::looks_like_letter(&id[end..])
+    {
+        return Reference {
+            contest_id: Some(id[..end].to_string()),
+            task: Some(id[end..].to_string()),
+            contest_guessed: false,
+        };
+    }
+    Reference {
+        contest_id: Some(id),
+        task: None,
+        contest_guessed: false,
+    }
+}
+
+/// 2語目: "C" のような記号はそのまま、"abc335_c" のような問題IDは小文字にする
+fn task_part(contest: &str, task: &str) -> String {
+    if crate::contest::looks_like_letter(task) {
+        task.to_string()
+    } else if task.contains('_') {
+        normalize_id(task)
+    } else {
+        // "abc335 abc335c" のような書き方にも一応対応する
+        let task = normalize_id(task);
+        let contest = normalize_id(contest);
+        match task.strip_prefix(contest.as_str()) {
+            Some(letter) if crate::contest::looks_like_letter(letter) => letter.to_string(),
+            _ => task,
+        }
+    }
+}
+
+/// AtCoder の ID は小文字 (ABC335 → abc335)
+fn normalize_id(text: &str) -> String {
+    text.trim().to_ascii_lowercase()
+}
+
+/// ツールの引数 (problem / contest_id / problem_id) から問題を決める
+///
+/// problem で足りない部分は contest_id / problem_id で補う ("C" と contest_id の組み合わせなど)。
+/// 両方で指定されたコンテストが食い違うときはエラーにする。
+pub fn from_args(
+    problem: Option<&str>,
+    contest_id: Option<&str>,
+    problem_id: Option<&str>,
+) -> anyhow::Result<ProblemRef> {
+    let reference = match problem {
+        Some(problem) => parse(problem)?,
+        None => Reference {
+            contest_id: None,
+            task: None,
+            contest_guessed: false,
+        },
+    };
+    let contest = match (reference.contest_guessed, contest_id) {
+        (true, Some(explicit)) => Some(normalize_id(explicit)),
+        _ => merge_contest(reference.contest_id, contest_id)?,
+    };
+    // 問題は problem の方を優先する (記号と問題IDは問題一覧を見るまで比べられない)
+    let task = reference
+        .task
+        .or_else(|| problem_id.map(|id| id.trim().to_string()));
+    match (contest, task) {
+        (Some(contest_id), Some(problem_id)) => Ok(ProblemRef {
+            contest_id,
+            problem_id,
+        }),
+        (_, None) if problem.is_some() => anyhow::bail!(
+            "{:?} does not name a task. Add the task letter ({})",
+            problem.unwrap_or_default(),
+            EXAMPLES
+        ),
+        _ => anyhow::bail!(
+            "Specify the problem with `problem` ({}) or with both contest_id and problem_id",
+            EXAMPLES
+        ),
+    }
+}
+
+/// stress_test のように問題の指定が任意のツール用 (何も指定されなければ None)
+pub fn optional_from_args(
+    problem: Option<&str>,
+    contest_id: Option<&str>,
+    problem_id: Option<&str>,
+) -> anyhow::Result<Option<ProblemRef>> {
+    if problem.is_none() && contest_id.is_none() && problem_id.is_none() {
+        return Ok(None);
+    }
+    from_args(problem, contest_id, problem_id).map(Some)
+}
+
+/// コンテストだけ分かればよいツール用 (problem にはコンテストの URL や "ABC335" も渡せる)
+pub fn contest_from_args(
+    problem: Option<&str>,
+    contest_id: Option<&str>,
+) -> anyhow::Result<String> {
+    let parsed = problem.map(parse).transpose()?.and_then(|r| r.contest_id);
+    merge_contest(parsed, contest_id)?.ok_or_else(|| {
+        anyhow::anyhow!("Specify the contest with contest_id or `problem` (e.g. \"ABC335\")")
+    })
+}
+
+fn merge_contest(parsed: Option<String>, explicit: Option<&str>) -> anyhow::Result<Option<String>> {
+    match (parsed, explicit.map(normalize_id)) {
+        (Some(parsed), Some(explicit)) if parsed != explicit => anyhow::bail!(
+            "`problem` names contest {} but contest_id is {}",
+            parsed,
+            explicit
+        ),
+        (parsed, explicit) => Ok(parsed.or(explicit)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problem(contest_id: &str, problem_id: &str) -> ProblemRef {
+        ProblemRef {
+            contest_id: contest_id.to_string(),
+            problem_id: problem_id.to_string(),
+        }
+    }
+
+    #[test]
+    fn parses_contest_and_letter() {
+        let reference = parse("ABC335 C").unwrap();
+        assert_eq!(reference.contest_id.as_deref(), Some("abc335"));
+        assert_eq!(reference.task.as_deref(), Some("C"));
+        assert!(!reference.contest_guessed);
+
+        let reference = parse("abc335/c").unwrap();
+        assert_eq!(reference.contest_id.as_deref(), Some("abc335"));
+        assert_eq!(reference.task.as_deref(), Some("c"));
+    }
+
+    #[test]
+    fn parses_joined_letter() {
+        let reference = parse("abc335c").unwrap();
+        assert_eq!(reference.contest_id.as_deref(), Some("abc335"));
+        assert_eq!(reference.task.as_deref(), Some("c"));
+
+        let reference = parse("ABC335Ex").unwrap();
+        assert_eq!(reference.contest_id.as_deref(), Some("abc335"));
+        assert_eq!(reference.task.as_deref(), Some("ex"));
+    }
+
+    #[test]
+    fn parses_problem_id_with_guessed_contest() {
+        let reference = parse("abc335_c").unwrap();
+        assert_eq!(reference.contest_id.as_deref(), Some("abc335"));
+        assert_eq!(reference.task.as_deref(), Some("abc335_c"));
+        assert!(reference.contest_guessed);
+    }
+
+    #[test]
+    fn parses_contest_only_and_letter_only() {
+        let reference = parse("ABC335").unwrap();
+        assert_eq!(reference.contest_id.as_deref(), Some("abc335"));
+        assert_eq!(reference.task, None);
+
+        let reference = parse("C").unwrap();
+        assert_eq!(reference.contest_id, None);
+        assert_eq!(reference.task.as_deref(), Some("C"));
+    }
+
+    #[test]
+    fn parses_urls() {
+        let reference = parse("https://atcoder.jp/contests/abc335/tasks/abc335_c?lang=en").unwrap();
+        assert_eq!(reference.contest_id.as_deref(), Some("abc335"));
+        assert_eq!(reference.task.as_deref(), Some("abc335_c"));
+        assert!(!reference.contest_guessed);
+
+        let reference = parse("atcoder.jp/contests/abc335/tasks/abc335_c#sample").unwrap();
+        assert_eq!(reference.task.as_deref(), Some("abc335_c"));
+
+        let reference = parse("https://atcoder.jp/contests/abc335?lang=en").unwrap();
+        assert_eq!(reference.contest_id.as_deref(), Some("abc335"));
+        assert_eq!(reference.task, None);
+    }
+
+    #[test]
+    fn rejects_bad_references() {
+        assert!(parse("").is_err());
+        assert!(parse("   ").is_err());
+        assert!(parse("abc335 c extra").is_err());
+        assert!(parse("https://example.com/contests/abc335/tasks/abc335_c").is_err());
+        assert!(parse("https://atcoder.jp/users/tourist").is_err());
+    }
+
+    #[test]
+    fn from_args_accepts_every_form() {
+        assert_eq!(
+            from_args(Some("ABC335 C"), None, None).unwrap(),
+            problem("abc335", "C")
+        );
+        assert_eq!(
+            from_args(Some("abc335c"), None, None).unwrap(),
+            problem("abc335", "c")
+        );
+        assert_eq!(
+            from_args(Some("abc335_c"), None, None).unwrap(),
+            problem("abc335", "abc335_c")
+        );
+        assert_eq!(
+            from_args(
+                Some("https://atcoder.jp/contests/abc335/tasks/abc335_c?lang=en"),
+                None,
+                None
+            )
+            .unwrap(),
+            problem("abc335", "abc335_c")
+        );
+        assert_eq!(
+            from_args(None, Some("ABC335"), Some("abc335_c")).unwrap(),
+            problem("abc335", "abc335_c")
+        );
+    }
+
+    #[test]
+    fn explicit_contest_overrides_guessed_one() {
+        // abc042 の C は arc058_a (同時開催のコンテストの問題ID)
+        assert_eq!(
+            from_args(Some("arc058_a"), Some("abc042"), None).unwrap(),
+            problem("abc042", "arc058_a")
+        );
+        assert_eq!(
+            from_args(Some("arc058_a"), None, None).unwrap(),
+            problem("arc058", "arc058_a")
+        );
+    }
+
+    #[test]
+    fn fills_missing_parts_from_other_arguments() {
+        assert_eq!(
+            from_args(Some("C"), Some("abc335"), None).unwrap(),
+            problem("abc335", "C")
+        );
+        assert_eq!(
+            from_args(Some("ABC335"), None, Some("C")).unwrap(),
+            problem("abc335", "C")
+        );
+    }
+
+    #[test]
+    fn rejects_conflicting_contest() {
+        let error = from_args(Some("ABC335 C"), Some("abc336"), None).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "`problem` names contest abc335 but contest_id is abc336"
+        );
+        assert!(
+            from_args(
+                Some("https://atcoder.jp/contests/abc335/tasks/abc335_c"),
+                Some("abc336"),
+                None
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn rejects_incomplete_arguments() {
+        assert!(from_args(None, None, None).is_err());
+        assert!(from_args(None, Some("abc335"), None).is_err());
+        assert!(from_args(Some("ABC335"), None, None).is_err());
+        assert!(from_args(Some("C"), None, None).is_err());
+    }
+
+    #[test]
+    fn optional_and_contest_only_arguments() {
+        assert_eq!(optional_from_args(None, None, None).unwrap(), None);
+        assert_eq!(
+            optional_from_args(Some("abc335c"), None, None).unwrap(),
+            Some(problem("abc335", "c"))
+        );
+        assert_eq!(contest_from_args(Some("ABC335"), None).unwrap(), "abc335");
+        assert_eq!(
+            contest_from_args(Some("https://atcoder.jp/contests/abc335/tasks"), None).unwrap(),
+            "abc335"
+        );
+        assert_eq!(contest_from_args(None, Some("ARC999")).unwrap(), "arc999");
+        assert!(contest_from_args(None, None).is_err());
+        assert!(contest_from_args(Some("abc335"), Some("abc336")).is_err());
+    }
+}
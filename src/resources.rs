@@ -0,0 +1,325 @@
+//! AtCoder の問題を MCP のリソースとして公開する
+//!
+//! URI は `atcoder://{contest_id}/{problem_id}/{statement|editorial}` の形。
+//! 読み込みは fetch_problem / fetch_editorial と同じ処理を使う。
+
+use crate::atcoder::AtCoder;
+use crate::config::Config;
+use crate::rpc::{INTERNAL_ERROR, RESOURCE_NOT_FOUND, RpcError};
+use serde_json::{Value, json};
+use std::sync::Mutex;
+
+const SCHEME: &str = "atcoder://";
+
+/// resources/list に載せる件数の上限
+const MAX_RECENT: usize = 50;
+
+/// リソースの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ResourceKind {
+    Statement,
+    Editorial,
+}
+
+impl ResourceKind {
+    fn from_segment(segment: &str) -> Option<Self> {
+        match segment {
+            "statement" => Some(Self::Statement),
+            "editorial" => Some(Self::Editorial),
+            _ => None,
+        }
+    }
+
+    /// 問題文も解説一覧も Markdown に変換して返す
+    fn mime_type(self) -> &'static str {
+        "text/markdown"
+    }
+
+    fn as_segment(self) -> &'static str {
+        match self {
+            Self::Statement => "statement",
+            Self::Editorial => "editorial",
+        }
+    }
+}
+
+/// `atcoder://` URI を分解したもの
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ResourceUri {
+    contest_id: String,
+    problem_id: String,
+    kind: ResourceKind,
+}
+
+impl ResourceUri {
+    fn parse(uri: &str) -> Option<Self> {
+        let rest = uri.strip_prefix(SCHEME)?;
+        let mut segments = rest.split('/');
+        let contest_id = segments.next().filter(|s| !s.is_empty())?;
+        let problem_id = segments.next().filter(|s| !s.is_empty())?;
+        let kind = ResourceKind::from_segment(segments.next()?)?;
+        if segments.next().is_some() {
+            return None;
+        }
+        Some(Self {
+            contest_id: contest_id.to_string(),
+            problem_id: problem_id.to_string(),
+            kind,
+        })
+    }
+
+    fn to_uri(&self) -> String {
+        format!(
+            "{}{}/{}/{}",
+            SCHEME,
+            self.contest_id,
+            self.problem_id,
+            self.kind.as_segment()
+        )
+    }
+
+    /// resources/list などに載せるメタデータ
+    fn describe(&self) -> Value {
+        let (name, description) = match self.kind {
+            ResourceKind::Statement => (
+                format!("{} 問題文", self.problem_id),
+                format!("{} の {} の問題文", self.contest_id, self.problem_id),
+            ),
+            ResourceKind::Editorial => (
+                format!("{} 解説", self.problem_id),
+                format!("{} の {} の解説一覧", self.contest_id, self.problem_id),
+            ),
+        };
+        json!({
+            "uri": self.to_uri(),
+            "name": name,
+            "description": description,
+            "mimeType": self.kind.mime_type()
+        })
+    }
+}
+
+/// リソース関連のリクエストを処理する
+#[derive(Default)]
+pub struct Resources {
+    /// このセッションで読まれたリソース (新しい順)
+    recent: Mutex<Vec<ResourceUri>>,
+}
+
+impl Resources {
+    /// resources/templates/list の結果
+    pub fn templates(&self) -> Value {
+        json!({
+            "resourceTemplates": [
+                {
+                    "uriTemplate": "atcoder://{contest_id}/{problem_id}/statement",
+                    "name": "AtCoder 問題文",
+                    "description": "AtCoderの問題文 (Markdown)。contest_id (例: abc335) と problem_id (例: abc335_a) を埋めて使います。",
+                    "mimeType": "text/markdown"
+                },
+                {
+                    "uriTemplate": "atcoder://{contest_id}/{problem_id}/editorial",
+                    "name": "AtCoder 解説",
+                    "description": "AtCoderの解説一覧 (公式解説とユーザー解説の著者・言語・URL)。開催中のコンテストや、終わったか確かめられないコンテストのものは読めません。",
+                    "mimeType": "text/markdown"
+                }
+            ]
+        })
+    }
+
+    /// resources/list の結果
+    /// 問題は無数にあるので、一覧にはこのセッションで読んだものだけを載せる
+    /// (それ以外はテンプレートから URI を組み立ててもらう)
+    pub fn list(&self) -> Value {
+        let resources: Vec<Value> = self
+            .recent
+            .lock()
+            .unwrap()
+            .iter()
+            .map(ResourceUri::describe)
+            .collect();
+        json!({ "resources": resources })
+    }
+
+    /// resources/read の処理 (問題文の言語は設定の既定値に従う)
+    pub async fn read(
+        &self,
+        params: Option<Value>,
+        config: &Config,
+        atcoder: &AtCoder,
+    ) -> Result<Value, RpcError> {
+        let uri = params
+            .as_ref()
+            .and_then(|p| p.get("uri"))
+            .and_then(Value::as_str)
+            .ok_or_else(|| RpcError::invalid_params("Missing uri"))?;
+
+        let resource = ResourceUri::parse(uri).ok_or_else(|| RpcError {
+            code: RESOURCE_NOT_FOUND,
+            message: format!("Resource not found: {}", uri),
+            data: Some(json!({ "uri": uri })),
+        })?;
+
+        let text = match resource.kind {
+            ResourceKind::Statement => atcoder
+                .fetch_problem(
+                    &resource.contest_id,
+                    &resource.problem_id,
+                    config.default_lang,
+                )
+                .await
+                .map(|page| page.markdown),
+            // 開催中のコンテストの解説は出さない (ツールと同じ確認)
+            ResourceKind::Editorial => {
+                async {
+                    atcoder
+                        .ensure_contest_finished(&resource.contest_id)
+                        .await?;
+                    let index = atcoder
+                        .fetch_editorial(&resource.contest_id, &resource.problem_id)
+                        .await?;
+                    anyhow::Ok(index.to_markdown())
+                }
+                .await
+            }
+        }
+        .map_err(|e| RpcError::new(INTERNAL_ERROR, format!("Failed to read {}: {}", uri, e)))?;
+
+        self.remember(&resource);
+
+        Ok(json!({
+            "contents": [{
+                "uri": resource.to_uri(),
+                "mimeType": resource.kind.mime_type(),
+                "text": text
+            }]
+        }))
+    }
+
+    fn remember(&self, resource: &ResourceUri) {
+        let mut recent = self.recent.lock().unwrap();
+        recent.retain(|r| r != resource);
+        recent.insert(0, resource.clone());
+        recent.truncate(MAX_RECENT);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::atcoder::tests::{PAGES, offline, running_contest_page};
+    use crate::rpc::INVALID_PARAMS;
+
+    #[test]
+    fn parses_uri() {
+        let uri = ResourceUri::parse("atcoder://abc335/abc335_c/editorial").unwrap();
+        assert_eq!(
+            uri,
+            ResourceUri {
+                contest_id: "abc335".to_string(),
+                problem_id: "abc335_c".to_string(),
+                kind: ResourceKind::Editorial,
+            }
+        );
+        assert_eq!(uri.to_uri(), "atcoder://abc335/abc335_c/editorial");
+        let uri = ResourceUri::parse("atcoder://abc335/abc335_c/statement").unwrap();
+        assert_eq!(uri.kind, ResourceKind::Statement);
+    }
+
+    #[test]
+    fn rejects_uri_with_missing_segments() {
+        for uri in [
+            "atcoder://abc335/statement",
+            "atcoder://abc335//statement",
+            "atcoder:///abc335_c/statement",
+            "atcoder://abc335/abc335_c",
+            "atcoder://abc335/abc335_c/",
+            "atcoder://",
+        ] {
+            assert_eq!(ResourceUri::parse(uri), None, "{}", uri);
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_kind_and_scheme() {
+        for uri in [
+            "atcoder://abc335/abc335_c/tests",
+            "atcoder://abc335/abc335_c/statement/extra",
+            "https://atcoder.jp/contests/abc335/tasks/abc335_c",
+        ] {
+            assert_eq!(ResourceUri::parse(uri), None, "{}", uri);
+        }
+    }
+
+    #[tokio::test]
+    async fn unknown_uri_is_resource_not_found() {
+        let atcoder = offline("resources-unknown", &[]).await;
+        let resources = Resources::default();
+        let uri = "atcoder://abc335/abc335_c/tests";
+        let error = resources
+            .read(Some(json!({ "uri": uri })), &Config::default(), &atcoder)
+            .await
+            .unwrap_err();
+        assert_eq!(error.code, RESOURCE_NOT_FOUND);
+        assert_eq!(error.code, -32002);
+        assert_eq!(error.data, Some(json!({ "uri": uri })));
+
+        let error = resources
+            .read(Some(json!({})), &Config::default(), &atcoder)
+            .await
+            .unwrap_err();
+        assert_eq!(error.code, INVALID_PARAMS);
+    }
+
+    #[tokio::test]
+    async fn reads_editorial_of_finished_contest() {
+        let atcoder = offline("resources-editorial", PAGES).await;
+        let resources = Resources::default();
+        let result = resources
+            .read(
+                Some(json!({ "uri": "atcoder://abc335/C/editorial" })),
+                &Config::default(),
+                &atcoder,
+            )
+            .await
+            .unwrap();
+        let content = &result["contents"][0];
+        assert_eq!(content["uri"], "atcoder://abc335/C/editorial");
+        assert_eq!(content["mimeType"], "text/markdown");
+        let text = content["text"].as_str().unwrap();
+        assert!(text.starts_with("# abc335_c の解説一覧"), "{}", text);
+        assert!(text.contains("https://atcoder.jp/contests/abc335/editorial/9041"));
+
+        // 読んだものは resources/list に載る
+        let list = resources.list();
+        assert_eq!(list["resources"][0]["uri"], "atcoder://abc335/C/editorial");
+        let _ = std::fs::remove_dir_all(atcoder.cache().dir().unwrap());
+    }
+
+    #[tokio::test]
+    async fn withholds_editorial_of_running_contest() {
+        let running = running_contest_page("ABC 998");
+        let atcoder = offline(
+            "resources-running",
+            &[("https://atcoder.jp/contests/abc998", &running)],
+        )
+        .await;
+        let resources = Resources::default();
+        let error = resources
+            .read(
+                Some(json!({ "uri": "atcoder://abc998/abc998_a/editorial" })),
+                &Config::default(),
+                &atcoder,
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(error.code, INTERNAL_ERROR);
+        assert!(
+            error.message.contains("ABC 998 is running until"),
+            "{}",
+            error.message
+        );
+        assert_eq!(resources.list(), json!({ "resources": [] }));
+        let _ = std::fs::remove_dir_all(atcoder.cache().dir().unwrap());
+    }
+}
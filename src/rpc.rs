@@ -0,0 +1,160 @@
+//! JSON-RPC 2.0 のメッセージ型とレスポンスの組み立て
+
+use crate::schema::ValidationError;
+use serde::{Deserialize, Deserializer};
+use serde_json::{Value, json};
+use tokio::sync::mpsc;
+
+// JSON-RPC 2.0 のエラーコード
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+/// initialize より前にリクエストが来たとき
+pub const SERVER_NOT_INITIALIZED: i64 = -32000;
+/// resources/read で URI が見つからないとき (MCP の仕様で決まっている番号)
+pub const RESOURCE_NOT_FOUND: i64 = -32002;
+
+/// MCPのリクエスト構造
+#[derive(Deserialize)]
+pub struct JsonRcpRequest {
+    pub jsonrpc: String,
+    pub method: String,
+    pub params: Option<Value>,
+    /// id が無ければ None (通知)。`"id": null` は Some(Value::Null) として区別する
+    #[serde(default, deserialize_with = "present")]
+    pub id: Option<Value>,
+}
+
+/// フィールドがあれば null でも Some にする (無いときは `default` で None になる)
+fn present<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Value>, D::Error> {
+    Value::deserialize(deserializer).map(Some)
+}
+
+/// JSON-RPC のエラーオブジェクト
+#[derive(Debug)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+    pub data: Option<Value>,
+}
+
+impl RpcError {
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+
+    pub fn invalid_params(message: impl Into<String>) -> Self {
+        Self::new(INVALID_PARAMS, message)
+    }
+}
+
+/// 引数検証のエラーは、どのフィールドが悪いかを data に載せて返す
+impl From<ValidationError> for RpcError {
+    fn from(e: ValidationError) -> Self {
+        Self {
+            code: INVALID_PARAMS,
+            message: e.to_string(),
+            data: Some(json!({
+                "field": e.field,
+                "reason": e.message
+            })),
+        }
+    }
+}
+
+/// 成功レスポンスを組み立てる
+pub fn success_response(id: Value, result: Value) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "result": result
+    })
+}
+
+/// エラーレスポンスを組み立てる (id が分からないときは null)
+pub fn error_response(id: Value, error: RpcError) -> Value {
+    let mut body = json!({
+        "code": error.code,
+        "message": error.message
+    });
+    if let Some(data) = error.data {
+        body["data"] = data;
+    }
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": body
+    })
+}
+
+/// サーバーからの通知メッセージを組み立てる (id なし)
+pub fn notification(method: &str, params: Value) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": method,
+        "params": params
+    })
+}
+
+/// stdout への書き込み口
+///
+/// 実際の書き込みは writer タスク1つだけが行うので、
+/// どのタスクから送っても1行ずつ混ざらずに出力される。
+#[derive(Clone)]
+pub struct Outgoing(mpsc::UnboundedSender<Value>);
+
+impl Outgoing {
+    pub fn channel() -> (Self, mpsc::UnboundedReceiver<Value>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        (Self(tx), rx)
+    }
+
+    /// メッセージを送る (writer が既に終了していたら捨てる)
+    pub fn send(&self, message: Value) {
+        let _ = self.0.send(message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(value: Value) -> JsonRcpRequest {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn tells_missing_id_from_null_id() {
+        let notification =
+            request(json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }));
+        assert_eq!(notification.id, None);
+
+        let null_id = request(json!({ "jsonrpc": "2.0", "id": null, "method": "ping" }));
+        assert_eq!(null_id.id, Some(Value::Null));
+
+        let numbered = request(json!({ "jsonrpc": "2.0", "id": 7, "method": "ping" }));
+        assert_eq!(numbered.id, Some(json!(7)));
+    }
+
+    #[test]
+    fn builds_error_response_with_data() {
+        let error = RpcError {
+            data: Some(json!({ "field": "level" })),
+            ..RpcError::invalid_params("bad level")
+        };
+        assert_eq!(
+            error_response(json!(1), error),
+            json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "error": { "code": INVALID_PARAMS, "message": "bad level", "data": { "field": "level" } }
+            })
+        );
+    }
+}
@@ -0,0 +1,170 @@
+//! 出力の判定 (組み込みの比較かチェッカープログラム)
+//!
+//! 「誤差 10^{-6} まで許容」の問題は `Comparison::Float`、
+//! 答えが複数ありうる問題はユーザーが書いたチェッカーで判定する。
+
+use super::compare::diff;
+use super::{Comparison, Limits, Program, Workspace};
+use anyhow::Context;
+
+/// チェッカーに渡すファイル名 (testlib と同じく `checker 入力 出力 想定解` の順で渡す)
+const INPUT_FILE: &str = "checker_input.txt";
+const OUTPUT_FILE: &str = "checker_output.txt";
+const ANSWER_FILE: &str = "checker_answer.txt";
+
+/// 判定方法
+pub enum Checker {
+    Compare(Comparison),
+    /// チェッカープログラム
+    /// 終了コード 0 なら AC、1 (testlib の WA) か 2 (PE) なら WA、それ以外はチェッカー自体の失敗
+    Program {
+        program: Program,
+        limits: Limits,
+        /// ビルドしたディレクトリ (チェッカーと一緒に消す)
+        workspace: Workspace,
+    },
+}
+
+/// 出力を判定した結果
+pub struct CheckResult {
+    pub accepted: bool,
+    /// WA のときの差分かチェッカーのメッセージ
+    pub message: Option<String>,
+}
+
+impl Checker {
+    /// 解答の出力 `actual` を判定する
+    pub async fn check(
+        &self,
+        input: &str,
+        expected: &str,
+        actual: &str,
+    ) -> anyhow::Result<CheckResult> {
+        match self {
+            Checker::Compare(comparison) => {
+                let accepted = comparison.matches(expected, actual);
+                Ok(CheckResult {
+                    accepted,
+                    message: (!accepted).then(|| diff(expected, actual)),
+                })
+            }
+            Checker::Program {
+                program,
+                limits,
+                workspace,
+            } => {
+                let files = [
+                    (INPUT_FILE, input),
+                    (OUTPUT_FILE, actual),
+                    (ANSWER_FILE, expected),
+                ];
+                let mut args = Vec::new();
+                for (name, content) in files {
+                    let path = workspace.path().join(name);
+                    tokio::fs::write(&path, content)
+                        .await
+                        .with_context(|| format!("Failed to write {}", path.display()))?;
+                    args.push(path.to_string_lossy().into_owned());
+                }
+
+                let execution = program.run_with_args(&args, "", limits).await?;
+                let mut message = execution.stderr.trim().to_string();
+                if message.is_empty() {
+                    message = execution.stdout.trim().to_string();
+                }
+                match execution.exit_code {
+                    Some(0) => Ok(CheckResult {
+                        accepted: true,
+                        message: None,
+                    }),
+                    Some(1 | 2) => Ok(CheckResult {
+                        accepted: false,
+                        message: (!message.is_empty()).then_some(message),
+                    }),
+                    code => anyhow::bail!(
+                        "Checker failed ({}): {}",
+                        code.map_or_else(
+                            || format!("{:?}", execution.termination),
+                            |c| format!("exit code {}", c)
+                        ),
+                        message
+                    ),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use crate::runner::tests::limits;
+
+    /// `sh -c script checker 入力 出力 想定解` で動くチェッカー
+    fn checker(script: &str) -> Checker {
+        let workspace = Workspace::create().unwrap();
+        let program = Program {
+            argv: ["sh", "-c", script, "checker"].map(String::from).to_vec(),
+            dir: workspace.path().to_path_buf(),
+            limit_address_space: false,
+        };
+        Checker::Program {
+            program,
+            limits: limits(),
+            workspace,
+        }
+    }
+
+    async fn check(script: &str) -> anyhow::Result<CheckResult> {
+        checker(script).check("in", "ans", "out").await
+    }
+
+    #[tokio::test]
+    async fn passes_files_in_testlib_order() {
+        let script =
+            r#"[ "$(cat "$1")" = in ] && [ "$(cat "$2")" = out ] && [ "$(cat "$3")" = ans ]"#;
+        let result = check(script).await.unwrap();
+        assert!(result.accepted);
+        assert_eq!(result.message, None);
+    }
+
+    #[tokio::test]
+    async fn exit_one_or_two_is_wrong_answer() {
+        let result = check("echo 'expected 3' >&2; exit 1").await.unwrap();
+        assert!(!result.accepted);
+        assert_eq!(result.message.as_deref(), Some("expected 3"));
+
+        // stderr が空なら stdout をメッセージにする
+        let result = check("echo 'presentation error'; exit 2").await.unwrap();
+        assert!(!result.accepted);
+        assert_eq!(result.message.as_deref(), Some("presentation error"));
+
+        let result = check("exit 1").await.unwrap();
+        assert_eq!(result.message, None);
+    }
+
+    #[tokio::test]
+    async fn other_exit_is_checker_error() {
+        let error = check("echo broken >&2; exit 3").await.err().unwrap();
+        assert_eq!(error.to_string(), "Checker failed (exit code 3): broken");
+
+        let error = check("kill -9 $$").await.err().unwrap();
+        assert!(
+            error.to_string().starts_with("Checker failed (Signaled)"),
+            "{}",
+            error
+        );
+    }
+
+    #[tokio::test]
+    async fn compares_without_program() {
+        let checker = Checker::Compare(Comparison::Tokens);
+        assert!(checker.check("", "1 2\n", "1  2").await.unwrap().accepted);
+        let result = Checker::Compare(Comparison::Exact)
+            .check("", "1 2\n", "1  2\n")
+            .await
+            .unwrap();
+        assert!(!result.accepted);
+        assert!(result.message.is_some());
+    }
+}
@@ -0,0 +1,181 @@
+//! 出力の比較と差分表示
+
+use crate::schema::SchemaType;
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+/// 差分表示に載せる行数の上限
+const MAX_DIFF_LINES: usize = 20;
+
+/// 比較方法の指定 (ツール引数用)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CompareMode {
+    /// 行ごとに比較する (行末の空白と末尾の空行は無視)
+    Exact,
+    /// 空白・改行の違いを無視してトークン列で比較する
+    Tokens,
+    /// 数値のトークンは誤差を許して比較する
+    Float,
+    /// チェッカープログラムで判定する
+    Checker,
+}
+
+impl SchemaType for CompareMode {
+    fn schema() -> Value {
+        json!({ "type": "string", "enum": ["exact", "tokens", "float", "checker"] })
+    }
+}
+
+/// 組み込みの比較方法
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+    Exact,
+    Tokens,
+    /// 絶対誤差または相対誤差がこの値以下なら一致とみなす
+    Float(f64),
+}
+
+impl std::fmt::Display for Comparison {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Comparison::Exact => write!(f, "exact"),
+            Comparison::Tokens => write!(f, "tokens"),
+            Comparison::Float(tolerance) => write!(f, "float (誤差 {:e})", tolerance),
+        }
+    }
+}
+
+impl Comparison {
+    /// 期待出力と実際の出力が一致するか
+    pub fn matches(self, expected: &str, actual: &str) -> bool {
+        match self {
+            Comparison::Exact => outputs_match(expected, actual),
+            Comparison::Tokens => expected.split_whitespace().eq(actual.split_whitespace()),
+            Comparison::Float(tolerance) => {
+                let expected: Vec<&str> = expected.split_whitespace().collect();
+                let actual: Vec<&str> = actual.split_whitespace().collect();
+                expected.len() == actual.len()
+                    && expected
+                        .iter()
+                        .zip(&actual)
+                        .all(|(e, a)| tokens_close(e, a, tolerance))
+            }
+        }
+    }
+}
+
+/// 両方が数値なら誤差を許して、そうでなければ文字列として比べる
+fn tokens_close(expected: &str, actual: &str, tolerance: f64) -> bool {
+    if expected == actual {
+        return true;
+    }
+    match (parse_number(expected), parse_number(actual)) {
+        (Some(e), Some(a)) => {
+            let error = (a - e).abs();
+            error <= tolerance || error <= tolerance * e.abs()
+        }
+        _ => false,
+    }
+}
+
+/// "nan" や "inf" は数値として扱わない (Rust の parse は受け付けてしまう)
+fn parse_number(token: &str) -> Option<f64> {
+    if !token.bytes().any(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    token.parse::<f64>().ok().filter(|x| x.is_finite())
+}
+
+/// 比較用に正規化した行 (行末の空白と末尾の空行は無視する)
+fn normalized_lines(text: &str) -> Vec<&str> {
+    let mut lines: Vec<&str> = text.lines().map(str::trim_end).collect();
+    while lines.last().is_some_and(|l| l.is_empty()) {
+        lines.pop();
+    }
+    lines
+}
+
+/// 期待出力と実際の出力が一致するか
+pub fn outputs_match(expected: &str, actual: &str) -> bool {
+    normalized_lines(expected) == normalized_lines(actual)
+}
+
+/// 食い違っている行を `- 期待` / `+ 実際` の形で並べる
+pub fn diff(expected: &str, actual: &str) -> String {
+    let expected = normalized_lines(expected);
+    let actual = normalized_lines(actual);
+    let mut out = Vec::new();
+    let mut shown = 0;
+
+    for i in 0..expected.len().max(actual.len()) {
+        let (e, a) = (expected.get(i), actual.get(i));
+        if e == a {
+            continue;
+        }
+        if shown == MAX_DIFF_LINES {
+            out.push("...".to_string());
+            break;
+        }
+        out.push(format!("line {}:", i + 1));
+        match e {
+            Some(e) => out.push(format!("- {}", e)),
+            None => out.push("- (no line)".to_string()),
+        }
+        match a {
+            Some(a) => out.push(format!("+ {}", a)),
+            None => out.push("+ (no line)".to_string()),
+        }
+        shown += 1;
+    }
+    out.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact_ignores_trailing_whitespace_only() {
+        assert!(Comparison::Exact.matches("1 2\n3\n", "1 2  \n3"));
+        assert!(Comparison::Exact.matches("Yes\n", "Yes\n\n\n"));
+        assert!(!Comparison::Exact.matches("1 2\n3\n", "1 2 3\n"));
+        assert!(!Comparison::Exact.matches("1  2\n", "1 2\n"));
+        assert!(!Comparison::Exact.matches("Yes\n", "yes\n"));
+    }
+
+    #[test]
+    fn tokens_ignores_layout() {
+        assert!(Comparison::Tokens.matches("1 2\n3\n", "1\n2 3"));
+        assert!(Comparison::Tokens.matches("1  2", " 1 2 "));
+        assert!(!Comparison::Tokens.matches("1 2 3", "1 2"));
+        assert!(!Comparison::Tokens.matches("1.0", "1"));
+    }
+
+    #[test]
+    fn float_allows_absolute_or_relative_error() {
+        let float = Comparison::Float(1e-6);
+        assert!(float.matches("0.333333333", "0.3333334"));
+        assert!(float.matches("1000000000", "1000000500"));
+        assert!(float.matches("1.5 Yes", "1.5000001\nYes"));
+        assert!(!float.matches("0.5", "0.51"));
+        assert!(!float.matches("1.5 Yes", "1.5 No"));
+        assert!(!float.matches("1 2", "1"));
+    }
+
+    #[test]
+    fn float_does_not_treat_nan_or_inf_as_numbers() {
+        let float = Comparison::Float(1e-6);
+        assert!(!float.matches("1", "nan"));
+        assert!(!float.matches("1", "inf"));
+        assert!(float.matches("inf", "inf"));
+    }
+
+    #[test]
+    fn diff_lists_mismatched_lines() {
+        let diff = diff("1\n2\n3\n", "1\n5\n");
+        assert!(diff.contains("line 2:"));
+        assert!(diff.contains("line 3:"));
+        assert!(!diff.contains("line 1:"));
+    }
+}
@@ -0,0 +1,529 @@
+//! インタラクティブ問題のローカルジャッジ
+//!
+//! 解答と判定プログラムを両方サンドボックスで起動し、
+//! 互いの標準出力を相手の標準入力へ1行ずつ中継する。
+//! 中継の途中でやり取りを記録し、クエリ数を数える。
+
+use super::sandbox::Cancel;
+use super::{CancelOnDrop, Execution, Judgement, Limits, Program, Verdict, limit_verdict};
+use anyhow::Context;
+use std::sync::{Arc, Mutex};
+
+/// やり取りの記録に残す行数の上限 (クエリ数は上限を超えても数え続ける)
+const MAX_TRANSCRIPT_LINES: usize = 1000;
+
+/// 記録に残す1行あたりの文字数の上限
+const MAX_LINE_LEN: usize = 200;
+
+/// 記録全体のバイト数の上限
+const MAX_TRANSCRIPT_BYTES: usize = 256 * 1024;
+
+/// 判定プログラムの stderr の上限 (WA の理由を読むためだけに使う)
+const MAX_JUDGE_STDERR_BYTES: u64 = 64 * 1024;
+
+/// どちらが送った行か
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Solution,
+    Judge,
+}
+
+impl Side {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Side::Solution => "solution",
+            Side::Judge => "judge",
+        }
+    }
+}
+
+/// 1回のやり取りの結果
+pub struct Session {
+    pub solution: Execution,
+    pub judge: Execution,
+    /// 送られた順の (送り手, 行)
+    pub transcript: Vec<(Side, String)>,
+    /// 記録を途中で打ち切ったか
+    pub truncated: bool,
+    /// 解答が送ったクエリの数
+    pub queries: u64,
+    /// クエリ数の上限を超えて止めたか
+    pub query_limit_exceeded: bool,
+}
+
+/// 中継スレッドが共有する記録
+#[derive(Default)]
+struct Transcript {
+    lines: Vec<(Side, String)>,
+    bytes: usize,
+    truncated: bool,
+    queries: u64,
+    query_limit_exceeded: bool,
+    /// 出力の上限を超えて止めた側
+    solution_output_exceeded: bool,
+    judge_output_exceeded: bool,
+}
+
+impl Transcript {
+    fn push(&mut self, side: Side, line: String) {
+        if self.lines.len() < MAX_TRANSCRIPT_LINES
+            && self.bytes + line.len() <= MAX_TRANSCRIPT_BYTES
+        {
+            self.bytes += line.len();
+            self.lines.push((side, line));
+        } else {
+            self.truncated = true;
+        }
+    }
+
+    fn output_exceeded(&mut self, side: Side) -> &mut bool {
+        match side {
+            Side::Solution => &mut self.solution_output_exceeded,
+            Side::Judge => &mut self.judge_output_exceeded,
+        }
+    }
+}
+
+/// クエリ数の数え方
+#[derive(Debug, Clone)]
+pub struct QueryLimit {
+    /// 上限 (None なら数えるだけ)
+    pub max: Option<u64>,
+    /// この文字列で始まる行をクエリとして数える (空なら解答が送った全行)
+    pub prefix: String,
+}
+
+impl QueryLimit {
+    /// 接頭辞を省略したら "?" で始まる行を数える (AtCoder のインタラクティブ問題の慣習)
+    pub fn new(max: Option<u64>, prefix: Option<String>) -> Self {
+        Self {
+            max,
+            prefix: prefix.unwrap_or_else(|| "?".to_string()),
+        }
+    }
+}
+
+/// 解答と判定プログラムをつないで実行する
+/// `judge_args` は判定プログラムの実行コマンドの後ろに足す引数 (テストケースのファイルなど)
+pub async fn interact(
+    solution: &Program,
+    judge: &Program,
+    judge_args: &[String],
+    limits: &Limits,
+    judge_limits: &Limits,
+    query_limit: &QueryLimit,
+) -> anyhow::Result<Session> {
+    let mut solution_limits = limits.clone();
+    solution_limits.limit_address_space &= solution.limit_address_space;
+    let mut judge_limits = judge_limits.clone();
+    judge_limits.limit_address_space &= judge.limit_address_space;
+    let solution = (solution.argv.clone(), solution.dir.clone());
+    let mut judge_argv = judge.argv.clone();
+    judge_argv.extend_from_slice(judge_args);
+    let judge = (judge_argv, judge.dir.clone());
+    let query_limit = query_limit.clone();
+    let cancel = Cancel::default();
+    let _guard = CancelOnDrop(cancel.clone());
+
+    tokio::task::spawn_blocking(move || {
+        run_session(
+            solution,
+            judge,
+            &solution_limits,
+            &judge_limits,
+            &query_limit,
+            &cancel,
+        )
+    })
+    .await
+    .context("Sandbox thread panicked")?
+}
+
+#[cfg(unix)]
+fn run_session(
+    (solution_argv, solution_dir): (Vec<String>, std::path::PathBuf),
+    (judge_argv, judge_dir): (Vec<String>, std::path::PathBuf),
+    limits: &Limits,
+    judge_limits: &Limits,
+    query_limit: &QueryLimit,
+    cancel: &Cancel,
+) -> anyhow::Result<Session> {
+    use super::sandbox::{MAX_STDERR_BYTES, Process, Watchdog, read_capped};
+
+    let mut solution = Process::spawn(&solution_argv, &solution_dir, limits)?;
+    let solution_killer = solution.killer();
+    let _solution_registration = cancel.register(solution_killer);
+    let mut judge = match Process::spawn(&judge_argv, &judge_dir, judge_limits) {
+        Ok(judge) => judge,
+        Err(e) => {
+            solution_killer.kill();
+            let _ = solution.wait();
+            return Err(e.context("Failed to start judge"));
+        }
+    };
+    let judge_killer = judge.killer();
+    let _judge_registration = cancel.register(judge_killer);
+    let killers = [solution_killer, judge_killer];
+
+    let take = |process: &mut Process| {
+        let child = &mut process.child;
+        Some((
+            child.stdin.take()?,
+            child.stdout.take()?,
+            child.stderr.take()?,
+        ))
+    };
+    let (Some((solution_in, solution_out, solution_err)), Some((judge_in, judge_out, judge_err))) =
+        (take(&mut solution), take(&mut judge))
+    else {
+        killers.iter().for_each(|k| k.kill());
+        anyhow::bail!("Failed to open pipes");
+    };
+
+    let transcript = Arc::new(Mutex::new(Transcript::default()));
+    let upstream = relay(
+        solution_out,
+        judge_in,
+        Side::Solution,
+        Arc::clone(&transcript),
+        Some(query_limit.clone()),
+        limits.output_bytes,
+        killers,
+    );
+    let downstream = relay(
+        judge_out,
+        solution_in,
+        Side::Judge,
+        Arc::clone(&transcript),
+        None,
+        judge_limits.output_bytes,
+        killers,
+    );
+    let solution_stderr = read_capped(solution_err, MAX_STDERR_BYTES, None);
+    let judge_stderr = read_capped(judge_err, MAX_JUDGE_STDERR_BYTES, None);
+
+    // 判定プログラムの待ちで止まった場合もまとめて止める
+    let watchdog = Watchdog::start(limits.wall_time, killers.to_vec());
+    let solution_exit = solution.wait();
+    let judge_exit = judge.wait();
+    let timed_out = watchdog.stop();
+    killers.iter().for_each(|k| k.kill());
+    let (solution_exit, judge_exit) = (solution_exit?, judge_exit?);
+
+    let _ = upstream.join();
+    let _ = downstream.join();
+    let (solution_stderr, _) = solution_stderr.join().unwrap_or_default();
+    let (judge_stderr, _) = judge_stderr.join().unwrap_or_default();
+
+    // 見張りが止めたのが解答の方かどうかは、解答が実時間の上限まで動いていたかで見分ける
+    let solution_timed_out = timed_out && solution_exit.elapsed >= limits.wall_time;
+    let transcript = std::mem::take(&mut *transcript.lock().unwrap());
+    Ok(Session {
+        solution: solution_exit.into_execution(
+            Vec::new(),
+            solution_stderr,
+            transcript.solution_output_exceeded,
+            solution_timed_out,
+        ),
+        judge: judge_exit.into_execution(
+            Vec::new(),
+            judge_stderr,
+            transcript.judge_output_exceeded,
+            timed_out,
+        ),
+        transcript: transcript.lines,
+        truncated: transcript.truncated,
+        queries: transcript.queries,
+        query_limit_exceeded: transcript.query_limit_exceeded,
+    })
+}
+
+#[cfg(not(unix))]
+fn run_session(
+    _solution: (Vec<String>, std::path::PathBuf),
+    _judge: (Vec<String>, std::path::PathBuf),
+    _limits: &Limits,
+    _judge_limits: &Limits,
+    _query_limit: &QueryLimit,
+    _cancel: &Cancel,
+) -> anyhow::Result<Session> {
+    anyhow::bail!("Running solutions is only supported on Unix")
+}
+
+/// `from` から1行読むたびに記録して `to` へ流す
+/// 相手が先に終わって書き込めなくなっても、送り手が詰まらないように読み捨てを続ける
+/// 読んだ量が `max_bytes` を超えたら (改行の無い1行でも) 両方を止める
+#[cfg(unix)]
+fn relay(
+    from: impl std::io::Read + Send + 'static,
+    to: impl std::io::Write + Send + 'static,
+    side: Side,
+    transcript: Arc<Mutex<Transcript>>,
+    query_limit: Option<QueryLimit>,
+    max_bytes: u64,
+    killers: [super::sandbox::Killer; 2],
+) -> std::thread::JoinHandle<()> {
+    use std::io::{BufRead, Read};
+
+    std::thread::spawn(move || {
+        let mut reader = std::io::BufReader::new(from);
+        let mut to = Some(to);
+        let mut line = Vec::new();
+        let mut total = 0;
+        loop {
+            line.clear();
+            // 上限を1バイトでも超えたと分かれば十分なので、残りより1バイトだけ多く読む
+            let mut bounded = (&mut reader).take((max_bytes - total).saturating_add(1));
+            match bounded.read_until(b'\n', &mut line) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => total += n as u64,
+            }
+            if total > max_bytes {
+                *transcript.lock().unwrap().output_exceeded(side) = true;
+                killers.iter().for_each(|k| k.kill());
+                break;
+            }
+
+            let text = String::from_utf8_lossy(&line);
+            let text = text.trim_end_matches(['\n', '\r']);
+            {
+                let mut transcript = transcript.lock().unwrap();
+                transcript.push(side, shorten(text));
+                if let Some(limit) = &query_limit
+                    && text.starts_with(&limit.prefix)
+                {
+                    transcript.queries += 1;
+                    if limit.max.is_some_and(|max| transcript.queries > max) {
+                        transcript.query_limit_exceeded = true;
+                        killers.iter().for_each(|k| k.kill());
+                        break;
+                    }
+                }
+            }
+
+            if let Some(writer) = to.as_mut()
+                && (writer.write_all(&line).is_err() || writer.flush().is_err())
+            {
+                to = None;
+            }
+        }
+        // ここで `to` が閉じられ、相手は EOF を受け取る
+    })
+}
+
+/// 長すぎる行は先頭だけ記録する
+fn shorten(line: &str) -> String {
+    match line.char_indices().nth(MAX_LINE_LEN) {
+        Some((i, _)) => format!("{}...", &line[..i]),
+        None => line.to_string(),
+    }
+}
+
+/// やり取りの結果から判定する
+///
+/// 解答の TLE / MLE / OLE、クエリ数超過、解答の RE、判定プログラムの WA、AC の順に見る。
+/// (解答が落ちると判定プログラムも EOF で WA を返しがちなので、RE を先に見る)
+/// 判定プログラムは終了コード 0 で AC、1 か 2 で WA (testlib の interactor と同じ)。
+pub fn judge_session(session: &Session, limits: &Limits, query_limit: &QueryLimit) -> Judgement {
+    let judge_message = || {
+        let stderr = session.judge.stderr.trim();
+        (!stderr.is_empty()).then(|| stderr.to_string())
+    };
+    let solution_verdict = limit_verdict(&session.solution, limits);
+
+    let (verdict, message) = match solution_verdict {
+        Some(
+            verdict @ (Verdict::TimeLimitExceeded
+            | Verdict::MemoryLimitExceeded
+            | Verdict::OutputLimitExceeded),
+        ) => (verdict, None),
+        _ if session.query_limit_exceeded => (
+            Verdict::WrongAnswer,
+            Some(format!(
+                "Query limit exceeded: more than {} queries",
+                query_limit.max.unwrap_or_default()
+            )),
+        ),
+        Some(verdict) => (verdict, None),
+        None if matches!(session.judge.exit_code, Some(1 | 2)) => {
+            (Verdict::WrongAnswer, judge_message())
+        }
+        None if session.judge.exit_code == Some(0) => (Verdict::Accepted, None),
+        None => (
+            Verdict::JudgeError,
+            Some(format!(
+                "Judge exited abnormally ({:?}, exit code {:?}){}",
+                session.judge.termination,
+                session.judge.exit_code,
+                judge_message()
+                    .map(|m| format!(": {}", m))
+                    .unwrap_or_default()
+            )),
+        ),
+    };
+    Judgement { verdict, message }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::runner::Termination;
+    use std::time::Duration;
+
+    fn sh(script: &str) -> Program {
+        Program {
+            argv: vec!["sh".to_string(), "-c".to_string(), script.to_string()],
+            dir: std::env::temp_dir(),
+            limit_address_space: false,
+        }
+    }
+
+    fn limits() -> Limits {
+        Limits {
+            time: Duration::from_secs(2),
+            wall_time: Duration::from_secs(5),
+            memory_bytes: None,
+            limit_address_space: false,
+            output_bytes: 1 << 20,
+            extra_processes: None,
+        }
+    }
+
+    async fn run(solution: &str, judge: &str, query_limit: &QueryLimit) -> (Session, Judgement) {
+        let session = interact(
+            &sh(solution),
+            &sh(judge),
+            &[],
+            &limits(),
+            &limits(),
+            query_limit,
+        )
+        .await
+        .unwrap();
+        let judgement = judge_session(&session, &limits(), query_limit);
+        (session, judgement)
+    }
+
+    const ECHO_JUDGE: &str =
+        r#"echo 5; read q; echo $(( ${q#? } * 2 )); read a; [ "$a" = "! 10" ]"#;
+
+    #[tokio::test]
+    async fn relays_lines_in_order() {
+        let query_limit = QueryLimit::new(None, None);
+        let (session, judgement) = run(
+            r#"read n; echo "? $n"; read r; echo "! $r""#,
+            ECHO_JUDGE,
+            &query_limit,
+        )
+        .await;
+        assert_eq!(
+            judgement.verdict,
+            Verdict::Accepted,
+            "{:?}",
+            judgement.message
+        );
+        assert_eq!(
+            session.transcript,
+            [
+                (Side::Judge, "5".to_string()),
+                (Side::Solution, "? 5".to_string()),
+                (Side::Judge, "10".to_string()),
+                (Side::Solution, "! 10".to_string()),
+            ]
+        );
+        assert!(!session.truncated);
+        // 既定では "?" で始まる行だけがクエリ
+        assert_eq!(session.queries, 1);
+    }
+
+    #[tokio::test]
+    async fn judge_rejects_wrong_answer() {
+        let (_, judgement) = run(
+            r#"read n; echo "? $n"; read r; echo "! 0""#,
+            ECHO_JUDGE,
+            &QueryLimit::new(None, None),
+        )
+        .await;
+        assert_eq!(judgement.verdict, Verdict::WrongAnswer);
+    }
+
+    #[tokio::test]
+    async fn empty_prefix_counts_every_line() {
+        let (session, _) = run(
+            r#"read n; echo "? $n"; read r; echo "! $r""#,
+            ECHO_JUDGE,
+            &QueryLimit::new(None, Some(String::new())),
+        )
+        .await;
+        assert_eq!(session.queries, 2);
+    }
+
+    #[tokio::test]
+    async fn stops_when_query_limit_is_exceeded() {
+        let query_limit = QueryLimit::new(Some(3), None);
+        let (session, judgement) = run(
+            r#"read n; while :; do echo "? 1"; read r; done"#,
+            "echo 5; while read q; do echo 0; done",
+            &query_limit,
+        )
+        .await;
+        assert!(session.query_limit_exceeded);
+        assert_eq!(session.queries, 4);
+        assert_eq!(judgement.verdict, Verdict::WrongAnswer);
+        assert_eq!(
+            judgement.message.as_deref(),
+            Some("Query limit exceeded: more than 3 queries")
+        );
+    }
+
+    #[tokio::test]
+    async fn stops_solution_exceeding_output_limit() {
+        // 改行の無い出力も1行として溜め込まずに止める
+        for solution in ["yes", "head -c 10000000 /dev/zero"] {
+            let (session, judgement) = run(
+                solution,
+                "while read q; do :; done",
+                &QueryLimit::new(None, None),
+            )
+            .await;
+            assert_eq!(
+                session.solution.termination,
+                Termination::OutputLimit,
+                "{}",
+                solution
+            );
+            assert_eq!(
+                judgement.verdict,
+                Verdict::OutputLimitExceeded,
+                "{}",
+                solution
+            );
+            assert!(session.solution.elapsed < limits().wall_time);
+        }
+    }
+
+    #[test]
+    fn shortens_long_lines() {
+        assert_eq!(shorten("abc"), "abc");
+        let long = "あ".repeat(MAX_LINE_LEN + 1);
+        assert_eq!(shorten(&long), format!("{}...", "あ".repeat(MAX_LINE_LEN)));
+    }
+
+    #[test]
+    fn caps_transcript() {
+        let mut transcript = Transcript::default();
+        for _ in 0..MAX_TRANSCRIPT_LINES + 1 {
+            transcript.push(Side::Solution, "x".to_string());
+        }
+        assert_eq!(transcript.lines.len(), MAX_TRANSCRIPT_LINES);
+        assert!(transcript.truncated);
+
+        let mut transcript = Transcript::default();
+        let line = "x".repeat(MAX_LINE_LEN);
+        for _ in 0..MAX_TRANSCRIPT_BYTES / MAX_LINE_LEN + 1 {
+            transcript.push(Side::Judge, line.clone());
+        }
+        assert!(transcript.bytes <= MAX_TRANSCRIPT_BYTES);
+        assert!(transcript.truncated);
+    }
+}
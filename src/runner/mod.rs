@@ -0,0 +1,375 @@
+//! 解答プログラムのコンパイルと実行
+//!
+//! 言語ごとのコマンドは設定 (`Config::languages`) から取る。
+//! 作業ディレクトリは一時ディレクトリに作り、終わったら消す。
+//! 解答の実行は `sandbox` で資源を制限して行う。
+
+mod checker;
+mod compare;
+mod interactive;
+mod sandbox;
+
+pub use checker::Checker;
+pub use compare::{CompareMode, Comparison};
+pub use interactive::{QueryLimit, interact, judge_session};
+pub use sandbox::{Execution, Limits, Termination};
+
+use sandbox::Cancel;
+
+use crate::config::LanguageConfig;
+use anyhow::Context;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::os::unix::fs::DirBuilderExt;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::process::Command;
+
+/// コンパイルにかけてよい時間
+const COMPILE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// 実行ファイルの名前 (`{exe}` の展開先)
+const EXE_NAME: &str = "main.out";
+
+/// 一時ディレクトリの名前を選び直す回数
+const WORKSPACE_ATTEMPTS: u32 = 16;
+
+/// 1回の実行ごとの一時ディレクトリ。drop すると中身ごと消える
+pub struct Workspace {
+    dir: PathBuf,
+}
+
+impl Workspace {
+    /// 名前は推測できない乱数にし、既にあるディレクトリは使わない
+    /// (他のユーザーが先回りして作ったディレクトリに解答を書き込まないように)
+    pub fn create() -> anyhow::Result<Self> {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        for _ in 0..WORKSPACE_ATTEMPTS {
+            // RandomState はプロセスごとにランダムな鍵を持つので、ハッシュ値を乱数として使う
+            let mut hasher = RandomState::new().build_hasher();
+            hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+            let dir = std::env::temp_dir().join(format!(
+                "atcoder-mcp-{}-{:016x}",
+                std::process::id(),
+                hasher.finish()
+            ));
+            match Self::create_at(dir.clone()) {
+                Ok(workspace) => return Ok(workspace),
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+                Err(e) => {
+                    return Err(e).with_context(|| format!("Failed to create {}", dir.display()));
+                }
+            }
+        }
+        anyhow::bail!(
+            "Failed to create a unique working directory in {}",
+            std::env::temp_dir().display()
+        )
+    }
+
+    /// 自分だけが読み書きできるディレクトリを新しく作る (既にあれば AlreadyExists)
+    fn create_at(dir: PathBuf) -> std::io::Result<Self> {
+        std::fs::DirBuilder::new().mode(0o700).create(&dir)?;
+        Ok(Self { dir })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.dir
+    }
+}
+
+impl Drop for Workspace {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// 実行できる状態になったプログラム
+pub struct Program {
+    argv: Vec<String>,
+    dir: PathBuf,
+    /// RLIMIT_AS を掛けてよいか (言語の設定から)
+    limit_address_space: bool,
+}
+
+/// ビルドの結果
+pub enum Build {
+    Ready(Program),
+    /// コンパイルエラー (コンパイラの出力を持つ)
+    CompileError(String),
+}
+
+/// 判定
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Accepted,
+    WrongAnswer,
+    RuntimeError,
+    TimeLimitExceeded,
+    MemoryLimitExceeded,
+    OutputLimitExceeded,
+    /// 判定プログラム自体が異常終了した (インタラクティブ)
+    JudgeError,
+}
+
+impl Verdict {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Verdict::Accepted => "AC",
+            Verdict::WrongAnswer => "WA",
+            Verdict::RuntimeError => "RE",
+            Verdict::TimeLimitExceeded => "TLE",
+            Verdict::MemoryLimitExceeded => "MLE",
+            Verdict::OutputLimitExceeded => "OLE",
+            Verdict::JudgeError => "JE",
+        }
+    }
+}
+
+/// `{src}` `{exe}` `{dir}` を展開する
+fn expand(args: &[String], source: &Path, dir: &Path) -> Vec<String> {
+    let exe = dir.join(EXE_NAME);
+    args.iter()
+        .map(|arg| {
+            arg.replace("{src}", &source.to_string_lossy())
+                .replace("{exe}", &exe.to_string_lossy())
+                .replace("{dir}", &dir.to_string_lossy())
+        })
+        .collect()
+}
+
+/// ソースを書き出し、必要ならコンパイルする
+pub async fn build(
+    language: &LanguageConfig,
+    source: &str,
+    workspace: &Workspace,
+) -> anyhow::Result<Build> {
+    let dir = workspace.path();
+    let source_path = dir.join(&language.source_file);
+    tokio::fs::write(&source_path, source)
+        .await
+        .with_context(|| format!("Failed to write {}", source_path.display()))?;
+
+    if let Some(compile) = &language.compile {
+        let argv = expand(compile, &source_path, dir);
+        let (program, args) = argv.split_first().context("Empty compile command")?;
+        let output = Command::new(program)
+            .args(args)
+            .current_dir(dir)
+            .stdin(Stdio::null())
+            .kill_on_drop(true)
+            .output();
+        let output = tokio::time::timeout(COMPILE_TIMEOUT, output)
+            .await
+            .context("Compilation timed out")?
+            .with_context(|| format!("Failed to run compiler `{}`", program))?;
+        if !output.status.success() {
+            let mut message = String::from_utf8_lossy(&output.stderr).into_owned();
+            message.push_str(&String::from_utf8_lossy(&output.stdout));
+            return Ok(Build::CompileError(message));
+        }
+    }
+
+    Ok(Build::Ready(Program {
+        argv: expand(&language.run, &source_path, dir),
+        dir: dir.to_path_buf(),
+        limit_address_space: language.limit_address_space,
+    }))
+}
+
+impl Program {
+    /// 標準入力に `input` を与え、`limits` の制限をかけて実行する
+    pub async fn run(&self, input: &str, limits: &Limits) -> anyhow::Result<Execution> {
+        self.run_with_args(&[], input, limits).await
+    }
+
+    /// 実行コマンドの後ろに `extra_args` を足して実行する (チェッカー用)
+    pub async fn run_with_args(
+        &self,
+        extra_args: &[String],
+        input: &str,
+        limits: &Limits,
+    ) -> anyhow::Result<Execution> {
+        let mut argv = self.argv.clone();
+        argv.extend_from_slice(extra_args);
+        let dir = self.dir.clone();
+        let input = input.to_string();
+        let mut limits = limits.clone();
+        limits.limit_address_space &= self.limit_address_space;
+        // wait4 やパイプの読み書きはブロッキングなので専用スレッドで
+        let cancel = Cancel::default();
+        let _guard = CancelOnDrop(cancel.clone());
+        tokio::task::spawn_blocking(move || sandbox::execute(&argv, &dir, &input, &limits, &cancel))
+            .await
+            .context("Sandbox thread panicked")?
+    }
+}
+
+/// 待っている future ごと捨てられたら (要求が中断されたら) 実行中のプロセスを止める
+/// 終わった後に drop されても、登録が外れているので何もしない
+struct CancelOnDrop(Cancel);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.cancel();
+    }
+}
+
+/// メモリ制限に張り付いて落ちたものは MLE とみなす割合
+const MLE_THRESHOLD: f64 = 0.9;
+
+/// メモリ確保に失敗したときに各言語の処理系が出すメッセージ
+/// (RLIMIT_AS で確保に失敗すると、RSS が制限に届く前に異常終了する)
+const OUT_OF_MEMORY_MARKERS: &[&str] = &[
+    "std::bad_alloc",
+    "MemoryError",
+    "memory allocation of",
+    "Cannot allocate memory",
+    "out of memory",
+];
+
+/// 判定の結果
+pub struct Judgement {
+    pub verdict: Verdict,
+    /// WA のときの差分かチェッカーのメッセージ
+    pub message: Option<String>,
+}
+
+/// 実行時間・メモリ・出力量の制限と異常終了だけを見た判定
+/// 制限内で正常終了していれば None (出力の比較へ進む)
+pub fn limit_verdict(execution: &Execution, limits: &Limits) -> Option<Verdict> {
+    let memory_ratio = match (execution.peak_rss_bytes, limits.memory_bytes) {
+        (Some(rss), Some(limit)) if limit > 0 => rss as f64 / limit as f64,
+        _ => 0.0,
+    };
+    let out_of_memory = memory_ratio >= MLE_THRESHOLD
+        || OUT_OF_MEMORY_MARKERS
+            .iter()
+            .any(|m| execution.stderr.contains(m));
+    match execution.termination {
+        Termination::OutputLimit => Some(Verdict::OutputLimitExceeded),
+        Termination::TimeLimit => Some(Verdict::TimeLimitExceeded),
+        _ if memory_ratio > 1.0 => Some(Verdict::MemoryLimitExceeded),
+        _ if execution.cpu_time > limits.time => Some(Verdict::TimeLimitExceeded),
+        _ if !execution.success() && out_of_memory => Some(Verdict::MemoryLimitExceeded),
+        _ if !execution.success() => Some(Verdict::RuntimeError),
+        _ => None,
+    }
+}
+
+/// 実行結果を判定する
+/// 制限を超えていなければ、`checker` で期待出力と比べる
+pub async fn judge(
+    execution: &Execution,
+    limits: &Limits,
+    checker: &Checker,
+    input: &str,
+    expected: &str,
+) -> anyhow::Result<Judgement> {
+    if let Some(verdict) = limit_verdict(execution, limits) {
+        return Ok(Judgement {
+            verdict,
+            message: None,
+        });
+    }
+    let result = checker.check(input, expected, &execution.stdout).await?;
+    Ok(Judgement {
+        verdict: if result.accepted {
+            Verdict::Accepted
+        } else {
+            Verdict::WrongAnswer
+        },
+        message: result.message,
+    })
+}
+
+#[cfg(all(test, target_os = "linux"))]
+pub(super) mod tests {
+    use super::*;
+
+    /// プロセスが生きているか (ゾンビは止まったものとみなす)
+    pub(in crate::runner) fn process_alive(pid: &str) -> bool {
+        std::fs::read_to_string(format!("/proc/{}/stat", pid.trim()))
+            .ok()
+            .and_then(|stat| Some(stat.rsplit_once(") ")?.1.starts_with('Z')))
+            .is_some_and(|zombie| !zombie)
+    }
+
+    /// 止まるまで少し待つ
+    pub(in crate::runner) fn wait_until_dead(pid: &str) -> bool {
+        (0..100).any(|_| {
+            std::thread::sleep(Duration::from_millis(20));
+            !process_alive(pid)
+        })
+    }
+
+    pub(in crate::runner) fn limits() -> Limits {
+        Limits {
+            time: Duration::from_secs(2),
+            wall_time: Duration::from_secs(10),
+            memory_bytes: None,
+            limit_address_space: false,
+            output_bytes: 1 << 20,
+            extra_processes: None,
+        }
+    }
+
+    #[test]
+    fn workspace_is_private_and_removed_on_drop() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let workspace = Workspace::create().unwrap();
+        let dir = workspace.path().to_path_buf();
+        let mode = std::fs::metadata(&dir).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+        assert!(dir.starts_with(std::env::temp_dir()));
+        std::fs::write(dir.join("main.cpp"), "int main() {}").unwrap();
+
+        let other = Workspace::create().unwrap();
+        assert_ne!(other.path(), dir);
+        drop(workspace);
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn workspace_refuses_existing_directory() {
+        let dir =
+            std::env::temp_dir().join(format!("atcoder-mcp-test-existing-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let error = Workspace::create_at(dir.clone()).err().unwrap();
+        assert_eq!(error.kind(), std::io::ErrorKind::AlreadyExists);
+        // 失敗したときは他人のディレクトリを消さない
+        assert!(dir.exists());
+        std::fs::remove_dir(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn aborting_run_kills_process_group() {
+        let workspace = Workspace::create().unwrap();
+        let program = Program {
+            argv: ["sh", "-c", "sleep 30 & echo $! > pid; wait"]
+                .map(String::from)
+                .to_vec(),
+            dir: workspace.path().to_path_buf(),
+            limit_address_space: false,
+        };
+        let task = tokio::spawn(async move { program.run("", &limits()).await });
+
+        let pid_file = workspace.path().join("pid");
+        let mut pid = String::new();
+        for _ in 0..100 {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            pid = std::fs::read_to_string(&pid_file).unwrap_or_default();
+            if pid.ends_with('\n') {
+                break;
+            }
+        }
+        assert!(process_alive(&pid), "{:?}", pid);
+
+        task.abort();
+        assert!(task.await.unwrap_err().is_cancelled());
+        assert!(wait_until_dead(&pid));
+    }
+}
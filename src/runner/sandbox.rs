@@ -0,0 +1,623 @@
+//! 資源制限つきでプログラムを実行する
+//!
+//! 解答 (ユーザーやモデルが書いたもの) が暴走しても MCP サーバーごと固まらないように、
+//! Linux の rlimit で CPU 時間・メモリ (RLIMIT_AS)・書き込みサイズ・プロセス数を制限し、
+//! 実時間の上限を超えたらプロセスグループごと止める。
+//! 実行後は wait4 の rusage から最大 RSS と CPU 時間を取る。
+
+use crate::config::SandboxConfig;
+use std::time::Duration;
+
+/// 1回の実行に課す制限
+#[derive(Debug, Clone)]
+pub struct Limits {
+    /// 問題の実行時間制限 (これを CPU 時間で超えたら TLE)
+    pub time: Duration,
+    /// 実時間の上限 (sleep や入力待ちで止まったプログラムを打ち切る)
+    pub wall_time: Duration,
+    /// メモリ制限 (バイト)。RLIMIT_AS と MLE の判定に使う
+    pub memory_bytes: Option<u64>,
+    /// RLIMIT_AS を掛けるかどうか (JVM など仮想メモリを大きく確保する処理系では外す)
+    pub limit_address_space: bool,
+    /// 標準出力の上限 (バイト)
+    pub output_bytes: u64,
+    /// 新しく作ってよいプロセス (スレッド) の数
+    pub extra_processes: Option<u64>,
+}
+
+impl Limits {
+    /// 問題の制限 (取れなかったら設定の既定値) から実行時の制限を決める
+    pub fn for_problem(
+        time_limit_ms: Option<u64>,
+        memory_limit_mb: Option<u64>,
+        config: &SandboxConfig,
+    ) -> Self {
+        let time = Duration::from_millis(time_limit_ms.unwrap_or(config.default_time_limit_ms));
+        let memory_mb = memory_limit_mb.unwrap_or(config.default_memory_limit_mb);
+        Self {
+            time,
+            wall_time: time.mul_f64(config.wall_time_factor)
+                + Duration::from_millis(config.wall_time_margin_ms),
+            memory_bytes: Some(memory_mb * 1024 * 1024),
+            limit_address_space: true,
+            output_bytes: config.max_output_bytes,
+            extra_processes: config.max_processes,
+        }
+    }
+}
+
+/// どう終わったか
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Termination {
+    /// 自分で終了した (終了コードは問わない)
+    Exited,
+    /// シグナルで落ちた (RLIMIT_CPU の SIGXCPU を除く)
+    Signaled,
+    /// CPU 時間か実時間の上限で止めた
+    TimeLimit,
+    /// 出力が多すぎて止めた
+    OutputLimit,
+}
+
+/// 1回の実行結果
+#[derive(Debug, Clone)]
+pub struct Execution {
+    pub termination: Termination,
+    /// 終了コード (シグナルで落ちたときは None)
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+    /// 実時間
+    pub elapsed: Duration,
+    /// CPU 時間 (user + sys)
+    pub cpu_time: Duration,
+    /// 最大 RSS (バイト)
+    pub peak_rss_bytes: Option<u64>,
+}
+
+impl Execution {
+    /// 正常終了したか
+    pub fn success(&self) -> bool {
+        self.termination == Termination::Exited && self.exit_code == Some(0)
+    }
+}
+
+/// 標準エラー出力は診断用なのでこれだけ取っておけば十分
+pub(super) const MAX_STDERR_BYTES: u64 = 64 * 1024;
+
+/// 実行中のプロセスを外から止めるためのハンドル
+///
+/// spawn_blocking のスレッドは中断できないので、要求が中断されたら
+/// 起動済みのプロセスグループを止めて、スレッドが早く終わるようにする
+#[derive(Debug, Clone, Default)]
+pub struct Cancel(std::sync::Arc<std::sync::Mutex<CancelState>>);
+
+#[derive(Debug, Default)]
+struct CancelState {
+    cancelled: bool,
+    #[cfg(unix)]
+    killers: Vec<Killer>,
+}
+
+impl Cancel {
+    /// 登録済みのプロセスを止め、これから登録されるものもすぐ止める
+    pub fn cancel(&self) {
+        let mut state = self.0.lock().unwrap();
+        state.cancelled = true;
+        #[cfg(unix)]
+        state.killers.iter().for_each(|k| k.kill());
+    }
+
+    /// 止める対象に加える。返り値を drop すると外れる
+    #[cfg(unix)]
+    pub(super) fn register(&self, killer: Killer) -> Registration<'_> {
+        let mut state = self.0.lock().unwrap();
+        if state.cancelled {
+            killer.kill();
+        }
+        state.killers.push(killer);
+        Registration {
+            cancel: self,
+            killer,
+        }
+    }
+}
+
+/// `Cancel::register` の登録 (回収し終えたプロセスの pid を後から撃たないように外す)
+#[cfg(unix)]
+pub(super) struct Registration<'a> {
+    cancel: &'a Cancel,
+    killer: Killer,
+}
+
+#[cfg(unix)]
+impl Drop for Registration<'_> {
+    fn drop(&mut self) {
+        let mut state = self.cancel.0.lock().unwrap();
+        state.killers.retain(|k| *k != self.killer);
+    }
+}
+
+/// 制限をかけて `argv` を実行する (ブロッキングなので spawn_blocking から呼ぶ)
+#[cfg(unix)]
+pub fn execute(
+    argv: &[String],
+    dir: &std::path::Path,
+    input: &str,
+    limits: &Limits,
+    cancel: &Cancel,
+) -> anyhow::Result<Execution> {
+    use anyhow::Context;
+    use std::io::Write;
+
+    let mut process = Process::spawn(argv, dir, limits)?;
+    let killer = process.killer();
+    let _registration = cancel.register(killer);
+
+    // 入力を書き込みきる前に出力が詰まるとデッドロックするので、書き込みは別スレッドで
+    let mut stdin = process.child.stdin.take().context("Failed to open stdin")?;
+    let input = input.to_string();
+    let writer = std::thread::spawn(move || {
+        let _ = stdin.write_all(input.as_bytes());
+    });
+    let stdout = process
+        .child
+        .stdout
+        .take()
+        .context("Failed to open stdout")?;
+    let stdout_reader = read_capped(stdout, limits.output_bytes, Some(killer));
+    let stderr = process
+        .child
+        .stderr
+        .take()
+        .context("Failed to open stderr")?;
+    let stderr_reader = read_capped(stderr, MAX_STDERR_BYTES, None);
+
+    let watchdog = Watchdog::start(limits.wall_time, vec![killer]);
+    let exit = process.wait();
+    let timed_out = watchdog.stop();
+    let exit = exit?;
+    // 本体が終わっても子孫がパイプを握っていることがあるので、グループごと片付ける
+    killer.kill();
+
+    let _ = writer.join();
+    let (stdout, output_exceeded) = stdout_reader.join().unwrap_or_default();
+    let (stderr, _) = stderr_reader.join().unwrap_or_default();
+    Ok(exit.into_execution(stdout, stderr, output_exceeded, timed_out))
+}
+
+/// 制限をかけて起動したプロセス
+#[cfg(unix)]
+pub(super) struct Process {
+    pub(super) child: std::process::Child,
+    pid: libc::pid_t,
+    start: std::time::Instant,
+}
+
+/// プロセスグループごと SIGKILL する (他のスレッドに渡せるように Copy にしてある)
+#[cfg(unix)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct Killer(libc::pid_t);
+
+#[cfg(unix)]
+impl Killer {
+    pub(super) fn kill(self) {
+        // SAFETY: 負の pid はプロセスグループ宛て。シグナルを送るだけなので失敗しても害はない
+        unsafe {
+            libc::kill(-self.0, libc::SIGKILL);
+        }
+    }
+}
+
+/// wait4 で回収した終了状態
+#[cfg(unix)]
+pub(super) struct Exit {
+    status: libc::c_int,
+    usage: libc::rusage,
+    pub(super) elapsed: Duration,
+}
+
+#[cfg(unix)]
+impl Process {
+    /// stdin / stdout / stderr をパイプにして、rlimit を掛けた新しいプロセスグループで起動する
+    pub(super) fn spawn(
+        argv: &[String],
+        dir: &std::path::Path,
+        limits: &Limits,
+    ) -> anyhow::Result<Self> {
+        use anyhow::Context;
+        use std::os::unix::process::CommandExt;
+        use std::process::{Command, Stdio};
+
+        let (program, args) = argv.split_first().context("Empty run command")?;
+
+        let rlimits = rlimits_for(limits);
+        let mut command = Command::new(program);
+        command
+            .args(args)
+            .current_dir(dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        // SAFETY: fork 後 exec 前の子プロセスで、async-signal-safe な setpgid / setrlimit だけを呼ぶ
+        unsafe {
+            command.pre_exec(move || {
+                // 子孫プロセスもまとめて止められるように、新しいプロセスグループにする
+                if libc::setpgid(0, 0) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                for &(resource, soft, hard) in &rlimits {
+                    let limit = libc::rlimit {
+                        rlim_cur: soft,
+                        rlim_max: hard,
+                    };
+                    if libc::setrlimit(resource, &limit) != 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                }
+                Ok(())
+            });
+        }
+
+        let start = std::time::Instant::now();
+        let child = command
+            .spawn()
+            .with_context(|| format!("Failed to run `{}`", program))?;
+        let pid = child.id() as libc::pid_t;
+        Ok(Self { child, pid, start })
+    }
+
+    pub(super) fn killer(&self) -> Killer {
+        Killer(self.pid)
+    }
+
+    /// 終了を待って rusage ごと回収する
+    pub(super) fn wait(self) -> anyhow::Result<Exit> {
+        use anyhow::Context;
+
+        let mut status: libc::c_int = 0;
+        // SAFETY: rusage はゼロ初期化で有効な値
+        let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+        // SAFETY: 自分で spawn した子プロセスを1回だけ回収する (std の Child::wait は使わない)
+        let waited = unsafe { libc::wait4(self.pid, &mut status, 0, &mut usage) };
+        let elapsed = self.start.elapsed();
+        if waited < 0 {
+            return Err(std::io::Error::last_os_error()).context("wait4 failed");
+        }
+        Ok(Exit {
+            status,
+            usage,
+            elapsed,
+        })
+    }
+}
+
+#[cfg(unix)]
+impl Exit {
+    pub(super) fn into_execution(
+        self,
+        stdout: Vec<u8>,
+        stderr: Vec<u8>,
+        output_exceeded: bool,
+        timed_out: bool,
+    ) -> Execution {
+        let Exit {
+            status,
+            usage,
+            elapsed,
+        } = self;
+        let cpu_time = timeval_to_duration(usage.ru_utime) + timeval_to_duration(usage.ru_stime);
+        // Linux の ru_maxrss は KiB 単位
+        let peak_rss_bytes = u64::try_from(usage.ru_maxrss).ok().map(|kb| kb * 1024);
+
+        let signal = libc::WIFSIGNALED(status).then(|| libc::WTERMSIG(status));
+        let termination = if output_exceeded {
+            Termination::OutputLimit
+        } else if timed_out || signal == Some(libc::SIGXCPU) {
+            Termination::TimeLimit
+        } else if signal.is_some() {
+            Termination::Signaled
+        } else {
+            Termination::Exited
+        };
+
+        Execution {
+            termination,
+            exit_code: libc::WIFEXITED(status).then(|| libc::WEXITSTATUS(status)),
+            stdout: String::from_utf8_lossy(&stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&stderr).into_owned(),
+            elapsed,
+            cpu_time,
+            peak_rss_bytes,
+        }
+    }
+}
+
+/// 実時間の見張り: 時間内に止められなければプロセスグループごと止める
+#[cfg(unix)]
+pub(super) struct Watchdog {
+    done: std::sync::mpsc::Sender<()>,
+    thread: std::thread::JoinHandle<bool>,
+}
+
+#[cfg(unix)]
+impl Watchdog {
+    pub(super) fn start(wall_time: Duration, killers: Vec<Killer>) -> Self {
+        use std::sync::mpsc;
+
+        let (done, done_rx) = mpsc::channel::<()>();
+        let thread = std::thread::spawn(move || {
+            let fired = matches!(
+                done_rx.recv_timeout(wall_time),
+                Err(mpsc::RecvTimeoutError::Timeout)
+            );
+            if fired {
+                killers.iter().for_each(|k| k.kill());
+            }
+            fired
+        });
+        Self { done, thread }
+    }
+
+    /// 見張りをやめる。時間切れで止めていたら true
+    pub(super) fn stop(self) -> bool {
+        let _ = self.done.send(());
+        self.thread.join().unwrap_or(false)
+    }
+}
+
+/// パイプを `cap` バイトまで読む。超えたら true を返す
+/// `killer` があれば超えた時点でプロセスを止め、無ければ残りを捨てながら読み続ける
+#[cfg(unix)]
+pub(super) fn read_capped(
+    pipe: impl std::io::Read + Send + 'static,
+    cap: u64,
+    killer: Option<Killer>,
+) -> std::thread::JoinHandle<(Vec<u8>, bool)> {
+    use std::io::Read;
+
+    std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        let mut pipe = pipe.take(cap + 1);
+        let _ = pipe.read_to_end(&mut buf);
+        if buf.len() as u64 <= cap {
+            return (buf, false);
+        }
+        buf.truncate(cap as usize);
+        match killer {
+            Some(killer) => killer.kill(),
+            None => {
+                let _ = std::io::copy(&mut pipe.into_inner(), &mut std::io::sink());
+            }
+        }
+        (buf, true)
+    })
+}
+
+#[cfg(not(unix))]
+pub fn execute(
+    _argv: &[String],
+    _dir: &std::path::Path,
+    _input: &str,
+    _limits: &Limits,
+    _cancel: &Cancel,
+) -> anyhow::Result<Execution> {
+    anyhow::bail!("Running solutions is only supported on Unix")
+}
+
+#[cfg(unix)]
+fn timeval_to_duration(tv: libc::timeval) -> Duration {
+    Duration::from_secs(tv.tv_sec as u64) + Duration::from_micros(tv.tv_usec as u64)
+}
+
+/// 子プロセスに掛ける rlimit の一覧 (fork 前に計算しておく)
+#[cfg(unix)]
+fn rlimits_for(limits: &Limits) -> Vec<(RlimitResource, libc::rlim_t, libc::rlim_t)> {
+    let mut rlimits = Vec::new();
+
+    // CPU 時間は秒単位なので切り上げて1秒足す (実際の TLE 判定は rusage で行う)
+    // ハードリミットに届くと SIGKILL になるので、1秒ずらして先に SIGXCPU を受けられるようにする
+    let cpu_secs =
+        (limits.time.as_secs() + 1 + u64::from(limits.time.subsec_nanos() > 0)) as libc::rlim_t;
+    rlimits.push((libc::RLIMIT_CPU, cpu_secs, cpu_secs + 1));
+
+    if limits.limit_address_space
+        && let Some(memory) = limits.memory_bytes
+    {
+        let memory = memory as libc::rlim_t;
+        rlimits.push((libc::RLIMIT_AS, memory, memory));
+    }
+
+    // ファイルへの書き込みも出力と同じ量までに抑える
+    let output = limits.output_bytes as libc::rlim_t;
+    rlimits.push((libc::RLIMIT_FSIZE, output, output));
+
+    // RLIMIT_NPROC はユーザー単位なので、今ある分に上乗せする
+    // (数えた後に同じユーザーのプロセスが増減するので目安でしかない)。
+    // root には効かないので掛けず、fork 爆弾はウォッチドッグがプロセスグループごと止める
+    #[cfg(target_os = "linux")]
+    if let Some(extra) = limits.extra_processes
+        && !running_as_root()
+    {
+        let current = count_user_processes();
+        let nproc = (current + extra) as libc::rlim_t;
+        rlimits.push((libc::RLIMIT_NPROC, nproc, nproc));
+    }
+
+    rlimits
+}
+
+#[cfg(target_os = "linux")]
+fn running_as_root() -> bool {
+    // SAFETY: geteuid は失敗しない
+    unsafe { libc::geteuid() == 0 }
+}
+
+#[cfg(all(unix, target_os = "linux"))]
+type RlimitResource = libc::__rlimit_resource_t;
+
+#[cfg(all(unix, not(target_os = "linux")))]
+type RlimitResource = libc::c_int;
+
+/// 自分と同じユーザーで動いているプロセス (スレッド込み) の数
+#[cfg(target_os = "linux")]
+fn count_user_processes() -> u64 {
+    use std::os::unix::fs::MetadataExt;
+
+    // SAFETY: getuid は失敗しない
+    let uid = unsafe { libc::getuid() };
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return 0;
+    };
+    entries
+        .filter_map(Result::ok)
+        .filter(|e| {
+            e.file_name()
+                .to_string_lossy()
+                .bytes()
+                .all(|b| b.is_ascii_digit())
+        })
+        .filter(|e| e.metadata().is_ok_and(|m| m.uid() == uid))
+        .map(|e| std::fs::read_dir(e.path().join("task")).map_or(1, |tasks| tasks.count() as u64))
+        .sum()
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use crate::runner::tests::{limits, wait_until_dead};
+    use crate::runner::{Verdict, limit_verdict};
+
+    fn run(script: &str, input: &str, limits: &Limits) -> (Execution, Option<Verdict>) {
+        let argv = ["sh", "-c", script].map(String::from);
+        let execution = execute(
+            &argv,
+            &std::env::temp_dir(),
+            input,
+            limits,
+            &Cancel::default(),
+        )
+        .unwrap();
+        let verdict = limit_verdict(&execution, limits);
+        (execution, verdict)
+    }
+
+    #[test]
+    fn passes_input_and_collects_output() {
+        let (execution, verdict) = run("cat; echo err >&2", "1 2\n", &limits());
+        assert_eq!(verdict, None);
+        assert_eq!(execution.stdout, "1 2\n");
+        assert_eq!(execution.stderr, "err\n");
+        assert!(execution.success());
+        assert!(execution.peak_rss_bytes.is_some_and(|rss| rss > 0));
+    }
+
+    #[test]
+    fn non_zero_exit_is_runtime_error() {
+        let (execution, verdict) = run("exit 3", "", &limits());
+        assert_eq!(verdict, Some(Verdict::RuntimeError));
+        assert_eq!(execution.termination, Termination::Exited);
+        assert_eq!(execution.exit_code, Some(3));
+    }
+
+    #[test]
+    fn cpu_rlimit_is_time_limit_exceeded() {
+        let limits = Limits {
+            time: Duration::from_millis(500),
+            ..limits()
+        };
+        let (execution, verdict) = run("while :; do :; done", "", &limits);
+        assert_eq!(verdict, Some(Verdict::TimeLimitExceeded));
+        assert_eq!(execution.termination, Termination::TimeLimit);
+        assert_eq!(execution.exit_code, None);
+        // RLIMIT_CPU は切り上げて1秒足した2秒で SIGXCPU になる (実時間の上限より先)
+        assert!(
+            execution.cpu_time >= Duration::from_secs(1),
+            "{:?}",
+            execution.cpu_time
+        );
+        assert!(execution.elapsed < limits.wall_time);
+    }
+
+    #[test]
+    fn wall_time_watchdog_kills_process_group() {
+        let limits = Limits {
+            wall_time: Duration::from_millis(300),
+            ..limits()
+        };
+        let (execution, verdict) = run("sleep 30 & echo $!; wait", "", &limits);
+        assert_eq!(verdict, Some(Verdict::TimeLimitExceeded));
+        assert_eq!(execution.termination, Termination::TimeLimit);
+        assert!(execution.elapsed >= limits.wall_time);
+        assert!(execution.elapsed < Duration::from_secs(5));
+        assert!(execution.cpu_time < limits.time);
+        // 子孫のプロセスも止まっている
+        assert!(!execution.stdout.is_empty());
+        assert!(wait_until_dead(&execution.stdout));
+    }
+
+    #[test]
+    fn output_cap_is_output_limit_exceeded() {
+        let limits = Limits {
+            output_bytes: 4096,
+            ..limits()
+        };
+        let (execution, verdict) = run("yes", "", &limits);
+        assert_eq!(verdict, Some(Verdict::OutputLimitExceeded));
+        assert_eq!(execution.termination, Termination::OutputLimit);
+        assert_eq!(execution.stdout.len(), 4096);
+        assert!(execution.elapsed < limits.wall_time);
+    }
+
+    #[test]
+    fn address_space_rlimit_is_applied() {
+        let limits = Limits {
+            memory_bytes: Some(256 << 20),
+            limit_address_space: true,
+            ..limits()
+        };
+        let (execution, verdict) = run("ulimit -v", "", &limits);
+        assert_eq!(verdict, None);
+        assert_eq!(execution.stdout.trim(), (256 << 10).to_string());
+
+        // JVM などでは掛けない
+        let limits = Limits {
+            limit_address_space: false,
+            ..limits
+        };
+        let (execution, _) = run("ulimit -v", "", &limits);
+        assert_eq!(execution.stdout.trim(), "unlimited");
+    }
+
+    #[test]
+    fn out_of_memory_is_memory_limit_exceeded() {
+        let limits = Limits {
+            memory_bytes: Some(256 << 20),
+            ..limits()
+        };
+        // 確保に失敗した処理系のメッセージ
+        let (execution, verdict) = run("echo 'std::bad_alloc' >&2; exit 134", "", &limits);
+        assert_eq!(verdict, Some(Verdict::MemoryLimitExceeded));
+        assert_eq!(execution.exit_code, Some(134));
+
+        // 正常終了でも、測った RSS が制限を超えていれば MLE
+        let limits = Limits {
+            memory_bytes: Some(1024),
+            ..limits
+        };
+        let (execution, verdict) = run("exit 0", "", &limits);
+        assert_eq!(verdict, Some(Verdict::MemoryLimitExceeded));
+        assert!(execution.peak_rss_bytes.is_some_and(|rss| rss > 1024));
+    }
+
+    #[test]
+    fn cancel_kills_registered_and_later_processes() {
+        let cancel = Cancel::default();
+        cancel.cancel();
+        let argv = ["sh", "-c", "sleep 30"].map(String::from);
+        let execution = execute(&argv, &std::env::temp_dir(), "", &limits(), &cancel).unwrap();
+        assert_eq!(execution.termination, Termination::Signaled);
+        assert!(execution.elapsed < Duration::from_secs(5));
+        assert!(cancel.0.lock().unwrap().killers.is_empty());
+    }
+}
@@ -0,0 +1,521 @@
+//! ツール引数の型定義と inputSchema の生成・検証
+//!
+//! `tool_args!` で構造体を定義すると、同じ定義から
+//! serde の Deserialize 実装と tools/list に載せる JSON Schema の両方が作られる。
+//! 引数の検証もこのスキーマを使うので、宣言と実際のチェックがずれない。
+
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value, json};
+
+/// スキーマ上の型を表すトレイト (フィールドの型ごとに実装する)
+pub trait SchemaType {
+    /// このフィールドの JSON Schema
+    fn schema() -> Value;
+
+    /// 必須フィールドかどうか (Option のときだけ false)
+    fn required() -> bool {
+        true
+    }
+
+    /// 出力時に省略する値かどうか (Option の None)
+    fn is_absent(&self) -> bool {
+        false
+    }
+}
+
+impl SchemaType for String {
+    fn schema() -> Value {
+        json!({ "type": "string" })
+    }
+}
+
+impl SchemaType for bool {
+    fn schema() -> Value {
+        json!({ "type": "boolean" })
+    }
+}
+
+impl SchemaType for u32 {
+    fn schema() -> Value {
+        json!({ "type": "integer", "minimum": 0, "maximum": u32::MAX })
+    }
+}
+
+impl SchemaType for u64 {
+    fn schema() -> Value {
+        json!({ "type": "integer", "minimum": 0, "maximum": u64::MAX })
+    }
+}
+
+impl SchemaType for f64 {
+    fn schema() -> Value {
+        json!({ "type": "number" })
+    }
+}
+
+impl<T: SchemaType> SchemaType for Vec<T> {
+    fn schema() -> Value {
+        json!({ "type": "array", "items": T::schema() })
+    }
+}
+
+impl<T: SchemaType> SchemaType for Option<T> {
+    fn schema() -> Value {
+        T::schema()
+    }
+
+    fn required() -> bool {
+        false
+    }
+
+    fn is_absent(&self) -> bool {
+        self.is_none()
+    }
+}
+
+/// ツール引数として使える型
+pub trait ToolArgs: DeserializeOwned {
+    /// tools/list で公開する inputSchema
+    fn input_schema() -> Value;
+}
+
+/// 引数が無いツール用
+impl ToolArgs for () {
+    fn input_schema() -> Value {
+        json!({ "type": "object", "properties": {} })
+    }
+}
+
+/// 引数検証で見つかった問題 (どのフィールドが悪いかを持つ)
+#[derive(Debug)]
+pub struct ValidationError {
+    pub field: Option<String>,
+    pub message: String,
+}
+
+impl ValidationError {
+    fn new(field: Option<&str>, message: impl Into<String>) -> Self {
+        Self {
+            field: field.map(str::to_string),
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.field {
+            Some(field) => write!(f, "Invalid argument '{}': {}", field, self.message),
+            None => write!(f, "Invalid arguments: {}", self.message),
+        }
+    }
+}
+
+/// arguments をスキーマで検証してから型付きの構造体に変換する
+pub fn parse_args<T: ToolArgs>(args: Value) -> Result<T, ValidationError> {
+    // arguments 自体が省略されたときは空オブジェクト扱い
+    let args = if args.is_null() { json!({}) } else { args };
+    validate(&T::input_schema(), &args)?;
+    serde_json::from_value(args).map_err(|e| ValidationError::new(None, e.to_string()))
+}
+
+/// 最低限の JSON Schema 検証 (type / properties / required / enum / minLength / minimum / items)
+pub fn validate(schema: &Value, value: &Value) -> Result<(), ValidationError> {
+    validate_at(schema, value, None)
+}
+
+fn validate_at(schema: &Value, value: &Value, path: Option<&str>) -> Result<(), ValidationError> {
+    if let Some(ty) = schema.get("type").and_then(Value::as_str) {
+        let ok = match ty {
+            "object" => value.is_object(),
+            "string" => value.is_string(),
+            "boolean" => value.is_boolean(),
+            "integer" => value.is_i64() || value.is_u64(),
+            "number" => value.is_number(),
+            "array" => value.is_array(),
+            _ => true,
+        };
+        if !ok {
+            return Err(ValidationError::new(
+                path,
+                format!("expected {}, got {}", ty, type_name(value)),
+            ));
+        }
+    }
+
+    if let Some(choices) = schema.get("enum").and_then(Value::as_array)
+        && !choices.contains(value)
+    {
+        let list = choices
+            .iter()
+            .map(Value::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        return Err(ValidationError::new(
+            path,
+            format!("must be one of [{}]", list),
+        ));
+    }
+
+    if let Some(min) = schema.get("minLength").and_then(Value::as_u64)
+        && let Some(s) = value.as_str()
+        && (s.chars().count() as u64) < min
+    {
+        return Err(ValidationError::new(
+            path,
+            format!("must be at least {} character(s) long", min),
+        ));
+    }
+
+    if let Some(min) = schema.get("minimum").and_then(Value::as_f64)
+        && let Some(n) = value.as_f64()
+        && n < min
+    {
+        return Err(ValidationError::new(path, format!("must be >= {}", min)));
+    }
+
+    if let Some(max) = schema.get("maximum").and_then(Value::as_f64)
+        && let Some(n) = value.as_f64()
+        && n > max
+    {
+        return Err(ValidationError::new(path, format!("must be <= {}", max)));
+    }
+
+    if let (Some(items), Some(array)) = (schema.get("items"), value.as_array()) {
+        for (i, item) in array.iter().enumerate() {
+            let item_path = format!("{}[{}]", path.unwrap_or(""), i);
+            validate_at(items, item, Some(&item_path))?;
+        }
+    }
+
+    if let Some(object) = value.as_object() {
+        validate_object(schema, object, path)?;
+    }
+
+    Ok(())
+}
+
+fn validate_object(
+    schema: &Value,
+    object: &Map<String, Value>,
+    path: Option<&str>,
+) -> Result<(), ValidationError> {
+    let join = |key: &str| match path {
+        Some(p) => format!("{}.{}", p, key),
+        None => key.to_string(),
+    };
+
+    if let Some(required) = schema.get("required").and_then(Value::as_array) {
+        for key in required.iter().filter_map(Value::as_str) {
+            if object.get(key).is_none_or(Value::is_null) {
+                return Err(ValidationError::new(
+                    Some(&join(key)),
+                    "missing required argument",
+                ));
+            }
+        }
+    }
+
+    if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+        for (key, prop_schema) in properties {
+            match object.get(key) {
+                // 任意フィールドの null は「指定なし」とみなす
+                None | Some(Value::Null) => {}
+                Some(v) => validate_at(prop_schema, v, Some(&join(key)))?,
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// フィールドのスキーマに description と追加の制約を足す
+pub fn field_schema(base: Value, description: &str, extra: Value) -> Value {
+    let mut schema = base;
+    if let Some(obj) = schema.as_object_mut() {
+        obj.insert("description".into(), json!(description.trim()));
+        if let Some(extra) = extra.as_object() {
+            for (k, v) in extra {
+                obj.insert(k.clone(), v.clone());
+            }
+        }
+    }
+    schema
+}
+
+/// 埋め込んだ引数 (`#[flatten]`) の properties と required を外側の object のスキーマに足す
+pub fn merge_object_schema(schema: &mut Value, inner: Value) {
+    for key in ["properties", "required"] {
+        let (Some(outer), Some(inner)) = (schema.get_mut(key), inner.get(key)) else {
+            continue;
+        };
+        match (outer, inner) {
+            (Value::Object(outer), Value::Object(inner)) => {
+                outer.extend(inner.iter().map(|(k, v)| (k.clone(), v.clone())));
+            }
+            (Value::Array(outer), Value::Array(inner)) => outer.extend(inner.iter().cloned()),
+            _ => {}
+        }
+    }
+}
+
+/// フィールド一覧から object の JSON Schema を組み立てる (tool_args! / tool_output! の共通部分)
+macro_rules! object_schema {
+    ($( ($field:ident, $ty:ty, $doc:literal, $extra:expr) ),*) => {{
+        #[allow(unused_mut)]
+        let mut properties = serde_json::Map::new();
+        #[allow(unused_mut)]
+        let mut required: Vec<&str> = Vec::new();
+        $(
+            properties.insert(
+                stringify!($field).to_string(),
+                $crate::schema::field_schema(
+                    <$ty as $crate::schema::SchemaType>::schema(),
+                    $doc,
+                    $extra,
+                ),
+            );
+            if <$ty as $crate::schema::SchemaType>::required() {
+                required.push(stringify!($field));
+            }
+        )*
+        serde_json::json!({
+            "type": "object",
+            "properties": properties,
+            "required": required
+        })
+    }};
+}
+
+/// ツール引数の構造体と inputSchema を一か所で定義するマクロ
+///
+/// ```ignore
+/// tool_args! {
+///     pub struct FetchProblemArgs {
+///         #[flatten]
+///         problem: ProblemArgs,
+///         /// 問題文の言語 (ja / en / both)
+///         lang: Option<Lang>,
+///     }
+/// }
+/// ```
+///
+/// 各フィールドの doc コメントがそのまま description になる。
+/// `where { ... }` で minLength や enum などの制約を追加できる。
+/// 先頭の `#[flatten]` のフィールドは、別の `tool_args!` の引数をそのまま埋め込む
+/// (serde の flatten と同じで、JSON では同じ階層に並ぶ。description は埋め込む側のものを使う)。
+macro_rules! tool_args {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $(
+                #[flatten]
+                $flat:ident : $flat_ty:ty,
+            )*
+            $(
+                #[doc = $doc:literal]
+                $field:ident : $ty:ty $(where $extra:tt)?
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, serde::Deserialize)]
+        $vis struct $name {
+            $(
+                #[serde(flatten)]
+                pub $flat: $flat_ty,
+            )*
+            $(
+                #[doc = $doc]
+                pub $field: $ty,
+            )*
+        }
+
+        impl $crate::schema::ToolArgs for $name {
+            fn input_schema() -> serde_json::Value {
+                #[allow(unused_mut)]
+                let mut schema =
+                    object_schema!($( ($field, $ty, $doc, tool_args!(@extra $($extra)?)) ),*);
+                $(
+                    $crate::schema::merge_object_schema(
+                        &mut schema,
+                        <$flat_ty as $crate::schema::ToolArgs>::input_schema(),
+                    );
+                )*
+                schema
+            }
+        }
+    };
+    (@extra) => { serde_json::Value::Null };
+    (@extra $extra:tt) => { serde_json::json!($extra) };
+}
+
+/// ツールの構造化出力 (structuredContent) の型と outputSchema を一か所で定義するマクロ
+///
+/// 書き方は `tool_args!` と同じ。Serialize と SchemaType が実装されるので、
+/// `Vec<Sample>` のように他の出力型の中に入れ子にもできる。
+/// Option のフィールドは None のとき出力に含めない。
+macro_rules! tool_output {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $(
+                #[doc = $doc:literal]
+                $field:ident : $ty:ty
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, serde::Serialize)]
+        $vis struct $name {
+            $(
+                #[doc = $doc]
+                #[serde(skip_serializing_if = "crate::schema::SchemaType::is_absent")]
+                pub $field: $ty,
+            )*
+        }
+
+        impl $crate::schema::SchemaType for $name {
+            fn schema() -> serde_json::Value {
+                object_schema!($( ($field, $ty, $doc, serde_json::Value::Null) ),*)
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    tool_args! {
+        /// テスト用の引数
+        struct InnerArgs {
+            /// 名前
+            name: Option<String> where { "minLength": 1 },
+        }
+    }
+
+    tool_args! {
+        /// テスト用の引数
+        struct TestArgs {
+            #[flatten]
+            inner: InnerArgs,
+            /// 回数
+            count: u32 where { "minimum": 1, "maximum": 10 },
+            /// 言語
+            lang: Option<String> where { "enum": ["ja", "en"] },
+            /// 入力
+            cases: Option<Vec<String>>,
+        }
+    }
+
+    fn error_field(args: Value) -> Option<String> {
+        parse_args::<TestArgs>(args).unwrap_err().field
+    }
+
+    #[test]
+    fn builds_input_schema_with_flattened_fields() {
+        let schema = TestArgs::input_schema();
+        assert_eq!(schema["type"], "object");
+        assert_eq!(schema["required"], json!(["count"]));
+        assert_eq!(schema["properties"]["count"]["description"], "回数");
+        assert_eq!(schema["properties"]["count"]["maximum"], 10);
+        assert_eq!(schema["properties"]["name"]["minLength"], 1);
+        assert_eq!(schema["properties"]["cases"]["items"]["type"], "string");
+    }
+
+    #[test]
+    fn parses_valid_arguments() {
+        let args: TestArgs =
+            parse_args(json!({ "name": "abc", "count": 3, "cases": ["1", "2"] })).unwrap();
+        assert_eq!(args.inner.name.as_deref(), Some("abc"));
+        assert_eq!(args.count, 3);
+        assert_eq!(args.lang, None);
+        assert_eq!(args.cases, Some(vec!["1".to_string(), "2".to_string()]));
+    }
+
+    #[test]
+    fn treats_null_optional_fields_as_absent() {
+        let args: TestArgs = parse_args(json!({ "count": 1, "lang": null })).unwrap();
+        assert_eq!(args.lang, None);
+    }
+
+    #[test]
+    fn reports_the_invalid_field() {
+        assert_eq!(error_field(json!({})).as_deref(), Some("count"));
+        assert_eq!(
+            error_field(json!({ "count": null })).as_deref(),
+            Some("count")
+        );
+        assert_eq!(
+            error_field(json!({ "count": "3" })).as_deref(),
+            Some("count")
+        );
+        assert_eq!(error_field(json!({ "count": 0 })).as_deref(), Some("count"));
+        assert_eq!(
+            error_field(json!({ "count": 11 })).as_deref(),
+            Some("count")
+        );
+        assert_eq!(
+            error_field(json!({ "count": 1, "lang": "fr" })).as_deref(),
+            Some("lang")
+        );
+        assert_eq!(
+            error_field(json!({ "count": 1, "name": "" })).as_deref(),
+            Some("name")
+        );
+        assert_eq!(
+            error_field(json!({ "count": 1, "cases": ["1", 2] })).as_deref(),
+            Some("cases[1]")
+        );
+    }
+
+    #[test]
+    fn rejects_integers_out_of_range_with_field() {
+        tool_args! {
+            /// テスト用の引数
+            struct WideArgs {
+                /// 32ビット
+                small: Option<u32>,
+                /// 64ビット
+                large: Option<u64>,
+            }
+        }
+        let error = parse_args::<WideArgs>(json!({ "small": 4_294_967_296u64 })).unwrap_err();
+        assert_eq!(error.field.as_deref(), Some("small"));
+        let args: WideArgs = parse_args(json!({ "small": u32::MAX, "large": u64::MAX })).unwrap();
+        assert_eq!((args.small, args.large), (Some(u32::MAX), Some(u64::MAX)));
+        let error = parse_args::<WideArgs>(json!({ "large": -1 })).unwrap_err();
+        assert_eq!(error.field.as_deref(), Some("large"));
+    }
+
+    #[test]
+    fn rejects_non_object_arguments() {
+        let error = parse_args::<TestArgs>(json!([1, 2])).unwrap_err();
+        assert_eq!(error.field, None);
+        assert!(error.to_string().contains("expected object"));
+    }
+
+    #[test]
+    fn validates_integer_and_number_types() {
+        let schema = json!({ "type": "integer" });
+        assert!(validate(&schema, &json!(3)).is_ok());
+        assert!(validate(&schema, &json!(3.5)).is_err());
+        let schema = json!({ "type": "number", "minimum": 0 });
+        assert!(validate(&schema, &json!(3)).is_ok());
+        assert!(validate(&schema, &json!(0.5)).is_ok());
+        assert!(validate(&schema, &json!(-0.5)).is_err());
+    }
+}
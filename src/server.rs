@@ -0,0 +1,472 @@
+//! リクエストの振り分けとサーバー全体で共有する状態
+
+use crate::atcoder::AtCoder;
+use crate::config::Config;
+use crate::prompts;
+use crate::protocol::ProtocolVersion;
+use crate::resources::Resources;
+use crate::rpc::{
+    INTERNAL_ERROR, INVALID_REQUEST, JsonRcpRequest, METHOD_NOT_FOUND, Outgoing, PARSE_ERROR,
+    RpcError, SERVER_NOT_INITIALIZED, error_response, success_response,
+};
+use crate::tools::{self, ToolRegistry};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use tokio::task::{AbortHandle, JoinSet};
+
+/// 処理中リクエストの管理用キー (id を JSON 文字列化したもの。1 と "1" は区別される)
+fn request_key(id: &Value) -> String {
+    id.to_string()
+}
+
+/// 処理中のリクエスト1件
+struct InFlight {
+    id: Value,
+    handle: AbortHandle,
+}
+
+/// 全リクエストで共有するサーバーの状態
+pub struct Server {
+    registry: ToolRegistry,
+    resources: Resources,
+    config: Arc<Config>,
+    /// atcoder.jp へのアクセス (キャッシュを全リクエストで共有する)
+    atcoder: Arc<AtCoder>,
+    outgoing: Outgoing,
+    /// initialize で決まったプロトコルバージョン (未初期化なら None)
+    protocol: OnceLock<ProtocolVersion>,
+    /// 処理中のリクエスト (notifications/cancelled で止めるため)
+    in_flight: Mutex<HashMap<String, InFlight>>,
+}
+
+impl Server {
+    pub fn new(outgoing: Outgoing, config: Arc<Config>) -> anyhow::Result<Self> {
+        Ok(Self {
+            registry: tools::registry(),
+            resources: Resources::default(),
+            atcoder: Arc::new(AtCoder::new(&config)?),
+            config,
+            outgoing,
+            protocol: OnceLock::new(),
+            in_flight: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// 終了処理: 処理中のリクエストを全部止め、待っているクライアントにはエラーを返す
+    /// (HTTP 通信もタスクごと打ち切られる)
+    pub fn shutdown(&self) {
+        let in_flight = std::mem::take(&mut *self.in_flight.lock().unwrap());
+        for (_, request) in in_flight {
+            request.handle.abort();
+            self.outgoing.send(error_response(
+                request.id,
+                RpcError::new(INTERNAL_ERROR, "Server is shutting down"),
+            ));
+        }
+    }
+
+    /// 1行分のメッセージを処理する
+    ///
+    /// リクエストは1つずつタスクにして `tasks` に積むので、
+    /// 遅いスクレイピングが他のリクエストを待たせることはない。
+    pub fn handle_line(self: &Arc<Self>, line: &str, tasks: &mut JoinSet<()>) {
+        let req = match parse_request(line) {
+            Ok(req) => req,
+            Err(response) => {
+                self.outgoing.send(response);
+                return;
+            }
+        };
+
+        // id が無いものは通知なので返信しない (notifications/initialized など)
+        let Some(id) = req.id else {
+            self.handle_notification(&req.method, req.params);
+            return;
+        };
+        // null の id は JSON-RPC では使えない (通知と区別できないので Invalid Request で返す)
+        if id.is_null() {
+            self.outgoing.send(error_response(
+                Value::Null,
+                RpcError::new(INVALID_REQUEST, "Invalid Request: id must not be null"),
+            ));
+            return;
+        }
+
+        let key = request_key(&id);
+        // spawn してから登録し終わるまでロックを握っておく
+        // (タスクが先に終わって finish() が空振りするのを防ぐ)
+        let mut in_flight = self.in_flight.lock().unwrap();
+        if in_flight.contains_key(&key) {
+            self.outgoing.send(error_response(
+                id,
+                RpcError::new(INVALID_REQUEST, "Invalid Request: duplicate request id"),
+            ));
+            return;
+        }
+
+        let server = Arc::clone(self);
+        let task_key = key.clone();
+        let task_id = id.clone();
+        let handle = tasks.spawn(async move {
+            let response = match server.dispatch(&req.method, req.params).await {
+                Ok(result) => success_response(task_id, result),
+                Err(error) => error_response(task_id, error),
+            };
+            // キャンセル済みなら返信しない
+            if server.finish(&task_key) {
+                server.outgoing.send(response);
+            }
+        });
+        in_flight.insert(key, InFlight { id, handle });
+    }
+
+    /// 処理が終わったリクエストを管理対象から外す
+    /// まだ登録されていた (= キャンセルされていない) なら true
+    fn finish(&self, key: &str) -> bool {
+        self.in_flight.lock().unwrap().remove(key).is_some()
+    }
+
+    /// 通知の処理 (返信はしない)
+    fn handle_notification(&self, method: &str, params: Option<Value>) {
+        match method {
+            // 初期化完了通知。何もしなくてOK
+            "notifications/initialized" => {}
+
+            // リクエストのキャンセル。タスクごと止めるので HTTP 通信も途中で打ち切られる
+            "notifications/cancelled" => {
+                let Some(request_id) = params.as_ref().and_then(|p| p.get("requestId")) else {
+                    return;
+                };
+                if let Some(request) = self
+                    .in_flight
+                    .lock()
+                    .unwrap()
+                    .remove(&request_key(request_id))
+                {
+                    request.handle.abort();
+                }
+            }
+
+            // 知らない通知は無視してよい
+            _ => {}
+        }
+    }
+
+    /// initialize の処理: クライアントの要求からプロトコルバージョンを決める
+    fn initialize(&self, params: Option<Value>) -> Result<Value, RpcError> {
+        let requested = params
+            .as_ref()
+            .and_then(|p| p.get("protocolVersion"))
+            .and_then(Value::as_str)
+            .ok_or_else(|| RpcError::invalid_params("Missing protocolVersion"))?;
+        let protocol = ProtocolVersion::negotiate(requested);
+
+        if self.protocol.set(protocol).is_err() {
+            return Err(RpcError::new(
+                INVALID_REQUEST,
+                "Invalid Request: initialize was already called",
+            ));
+        }
+
+        Ok(json!({
+            "protocolVersion": protocol.as_str(),
+            "capabilities": {
+                "tools": {},
+                "resources": {},
+                "prompts": {}
+            },
+            "serverInfo": {
+                "name": "atcoder-hint-mcp",
+                "version": "0.1.0"
+            }
+        }))
+    }
+
+    /// メソッドごとの分岐
+    async fn dispatch(&self, method: &str, params: Option<Value>) -> Result<Value, RpcError> {
+        // initialize と ping 以外は初期化が終わるまで受け付けない
+        let protocol = match (method, self.protocol.get()) {
+            ("initialize" | "ping", _) => self
+                .protocol
+                .get()
+                .copied()
+                .unwrap_or(ProtocolVersion::LATEST),
+            (_, Some(&protocol)) => protocol,
+            (_, None) => {
+                return Err(RpcError::new(
+                    SERVER_NOT_INITIALIZED,
+                    format!(
+                        "Server not initialized: {} was sent before initialize",
+                        method
+                    ),
+                ));
+            }
+        };
+
+        match method {
+            // 1. 初期化リクエスト (Zedが最初に送ってくる)
+            "initialize" => self.initialize(params),
+
+            // 死活確認。いつ来ても空の結果を返す
+            "ping" => Ok(json!({})),
+
+            // 2. ツール一覧の要求 (Zed「どんな機能があるの？」)
+            "tools/list" => Ok(self.registry.list(protocol)),
+
+            // 3. ツールの実行
+            "tools/call" => {
+                self.registry
+                    .call(
+                        params,
+                        &self.outgoing,
+                        &self.config,
+                        &self.atcoder,
+                        protocol,
+                    )
+                    .await
+            }
+
+            // 4. リソース (問題文・解説を URI で直接読めるようにする)
+            "resources/list" => Ok(self.resources.list()),
+            "resources/templates/list" => Ok(self.resources.templates()),
+            "resources/read" => {
+                self.resources
+                    .read(params, &self.config, &self.atcoder)
+                    .await
+            }
+
+            // 5. プロンプト (ヒントの頼み方を揃えるためのテンプレート)
+            "prompts/list" => Ok(prompts::list()),
+            "prompts/get" => prompts::get(params, &self.config, &self.atcoder).await,
+
+            // 未知のメソッド
+            unknown => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("Method not found: {}", unknown),
+            )),
+        }
+    }
+}
+
+/// 1行分のメッセージを JSON-RPC のリクエストとして解釈する
+/// 解釈できなければ、そのまま返すべきエラーレスポンスを返す
+fn parse_request(line: &str) -> Result<JsonRcpRequest, Value> {
+    // JSON としてパースできなければ Parse error (id は分からないので null)
+    let value: Value = serde_json::from_str(line).map_err(|e| {
+        error_response(
+            Value::Null,
+            RpcError::new(PARSE_ERROR, format!("Parse error: {}", e)),
+        )
+    })?;
+
+    // id だけは先に拾っておく (Invalid Request でも分かる範囲で返すため)
+    let raw_id = value.get("id").cloned().unwrap_or(Value::Null);
+
+    match serde_json::from_value::<JsonRcpRequest>(value) {
+        Ok(r) if r.jsonrpc == "2.0" => Ok(r),
+        Ok(_) => Err(error_response(
+            raw_id,
+            RpcError::new(INVALID_REQUEST, "Invalid Request: jsonrpc must be \"2.0\""),
+        )),
+        Err(e) => Err(error_response(
+            raw_id,
+            RpcError::new(INVALID_REQUEST, format!("Invalid Request: {}", e)),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::LanguageConfig;
+    use crate::rpc::INVALID_PARAMS;
+    use std::time::Duration;
+    use tokio::sync::mpsc::UnboundedReceiver;
+
+    fn server() -> (Arc<Server>, UnboundedReceiver<Value>) {
+        let mut config = Config::default();
+        config.cache.enabled = false;
+        config.languages.insert(
+            "sh".to_string(),
+            LanguageConfig {
+                source_file: "main.sh".to_string(),
+                compile: None,
+                run: vec!["sh".to_string(), "{src}".to_string()],
+                limit_address_space: true,
+            },
+        );
+        let (outgoing, rx) = Outgoing::channel();
+        (
+            Arc::new(Server::new(outgoing, Arc::new(config)).unwrap()),
+            rx,
+        )
+    }
+
+    async fn recv(rx: &mut UnboundedReceiver<Value>) -> Value {
+        tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .expect("no response")
+            .unwrap()
+    }
+
+    /// リクエストを1つ送って返信を待つ
+    async fn request(
+        server: &Arc<Server>,
+        rx: &mut UnboundedReceiver<Value>,
+        tasks: &mut JoinSet<()>,
+        line: Value,
+    ) -> Value {
+        server.handle_line(&line.to_string(), tasks);
+        recv(rx).await
+    }
+
+    async fn initialize(
+        server: &Arc<Server>,
+        rx: &mut UnboundedReceiver<Value>,
+        tasks: &mut JoinSet<()>,
+    ) -> Value {
+        let line = json!({
+            "jsonrpc": "2.0",
+            "id": 0,
+            "method": "initialize",
+            "params": { "protocolVersion": "2025-06-18" }
+        });
+        request(server, rx, tasks, line).await
+    }
+
+    #[tokio::test]
+    async fn rejects_malformed_messages() {
+        let (server, mut rx) = server();
+        let mut tasks = JoinSet::new();
+
+        server.handle_line("{", &mut tasks);
+        let response = recv(&mut rx).await;
+        assert_eq!(
+            (&response["id"], &response["error"]["code"]),
+            (&Value::Null, &json!(PARSE_ERROR))
+        );
+
+        let null_id = json!({ "jsonrpc": "2.0", "id": null, "method": "ping" });
+        let response = request(&server, &mut rx, &mut tasks, null_id).await;
+        assert_eq!(response["id"], Value::Null);
+        assert_eq!(response["error"]["code"], INVALID_REQUEST);
+
+        let version = json!({ "jsonrpc": "1.0", "id": 1, "method": "ping" });
+        let response = request(&server, &mut rx, &mut tasks, version).await;
+        assert_eq!(
+            (&response["id"], &response["error"]["code"]),
+            (&json!(1), &json!(INVALID_REQUEST))
+        );
+        assert!(tasks.is_empty());
+    }
+
+    #[tokio::test]
+    async fn requires_initialize_except_ping() {
+        let (server, mut rx) = server();
+        let mut tasks = JoinSet::new();
+
+        let list = json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/list" });
+        let response = request(&server, &mut rx, &mut tasks, list.clone()).await;
+        assert_eq!(response["id"], 1);
+        assert_eq!(response["error"]["code"], SERVER_NOT_INITIALIZED);
+
+        let ping = json!({ "jsonrpc": "2.0", "id": "p", "method": "ping" });
+        let response = request(&server, &mut rx, &mut tasks, ping.clone()).await;
+        assert_eq!(
+            response,
+            json!({ "jsonrpc": "2.0", "id": "p", "result": {} })
+        );
+
+        let response = initialize(&server, &mut rx, &mut tasks).await;
+        assert_eq!(response["result"]["protocolVersion"], "2025-06-18");
+        let response = request(&server, &mut rx, &mut tasks, list).await;
+        assert!(response["result"]["tools"].is_array());
+        let response = request(&server, &mut rx, &mut tasks, ping).await;
+        assert_eq!(response["result"], json!({}));
+    }
+
+    #[tokio::test]
+    async fn rejects_second_initialize() {
+        let (server, mut rx) = server();
+        let mut tasks = JoinSet::new();
+
+        let missing = json!({ "jsonrpc": "2.0", "id": 0, "method": "initialize", "params": {} });
+        let response = request(&server, &mut rx, &mut tasks, missing).await;
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+
+        initialize(&server, &mut rx, &mut tasks).await;
+        let response = initialize(&server, &mut rx, &mut tasks).await;
+        assert_eq!(response["error"]["code"], INVALID_REQUEST);
+        assert_eq!(
+            response["error"]["message"],
+            "Invalid Request: initialize was already called"
+        );
+    }
+
+    #[tokio::test]
+    async fn unknown_method_is_method_not_found() {
+        let (server, mut rx) = server();
+        let mut tasks = JoinSet::new();
+        initialize(&server, &mut rx, &mut tasks).await;
+        let line = json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/frobnicate" });
+        let response = request(&server, &mut rx, &mut tasks, line).await;
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn rejects_duplicate_id_and_cancels_request() {
+        let (server, mut rx) = server();
+        let mut tasks = JoinSet::new();
+        initialize(&server, &mut rx, &mut tasks).await;
+        while tasks.join_next().await.is_some() {}
+
+        // 終わらない解答で stress_test を走らせておく
+        let slow = json!({
+            "jsonrpc": "2.0",
+            "id": 7,
+            "method": "tools/call",
+            "params": {
+                "name": "stress_test",
+                "arguments": {
+                    "language": "sh",
+                    "source": "sleep 30",
+                    "brute": "cat",
+                    "generator": "echo 1"
+                }
+            }
+        });
+        server.handle_line(&slow.to_string(), &mut tasks);
+
+        // 同じ id は処理中なので受け付けない (7 と "7" は別の id)
+        let duplicate = json!({ "jsonrpc": "2.0", "id": 7, "method": "ping" });
+        let response = request(&server, &mut rx, &mut tasks, duplicate.clone()).await;
+        assert_eq!(response["id"], 7);
+        assert_eq!(
+            response["error"]["message"],
+            "Invalid Request: duplicate request id"
+        );
+        let other = json!({ "jsonrpc": "2.0", "id": "7", "method": "ping" });
+        let response = request(&server, &mut rx, &mut tasks, other).await;
+        assert_eq!(response["result"], json!({}));
+        while tasks.try_join_next().is_some() {}
+
+        let cancel = json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/cancelled",
+            "params": { "requestId": 7 }
+        });
+        server.handle_line(&cancel.to_string(), &mut tasks);
+        let joined = tokio::time::timeout(Duration::from_secs(5), tasks.join_next())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(joined.unwrap_err().is_cancelled());
+        // キャンセルしたリクエストには返信しない
+        assert!(rx.try_recv().is_err());
+
+        // 止めた id はまた使える
+        let response = request(&server, &mut rx, &mut tasks, duplicate).await;
+        assert_eq!(response["result"], json!({}));
+    }
+}
@@ -0,0 +1,204 @@
+//! 日時の読み書き
+//!
+//! AtCoder のページにある「2024-01-06T22:40:00+09:00」や HTTP ヘッダーの日付を UNIX 秒にし、
+//! 表示用に書き戻す。外部のクレートを使わず、グレゴリオ暦の計算だけで済ませる。
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 現在時刻 (UNIX 秒)
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// 「2024-01-06T22:40:00+09:00」のような日時を UNIX 秒にする
+pub fn parse_datetime(text: &str) -> Option<i64> {
+    let text = text.trim();
+    let (date, rest) = text.split_once('T').or_else(|| text.split_once(' '))?;
+    let mut ymd = date.splitn(3, '-').map(str::parse::<i64>);
+    let (year, month, day) = (ymd.next()?.ok()?, ymd.next()?.ok()?, ymd.next()?.ok()?);
+
+    // 時刻とタイムゾーン (Z / +09:00 / +0900) を分ける
+    let (time, offset) = match rest.find(['+', '-', 'Z']) {
+        Some(i) => rest.split_at(i),
+        None => (rest, ""),
+    };
+    let mut hms = time
+        .split(':')
+        .map(|s| s.split('.').next().unwrap_or(s).parse::<i64>());
+    let hour = hms.next()?.ok()?;
+    let minute = hms.next().unwrap_or(Ok(0)).ok()?;
+    let second = hms.next().unwrap_or(Ok(0)).ok()?;
+
+    let offset_secs = match offset {
+        "" | "Z" => 0,
+        _ => {
+            let sign = if offset.starts_with('-') { -1 } else { 1 };
+            let digits: String = offset[1..].chars().filter(char::is_ascii_digit).collect();
+            let (h, m) = digits.split_at(digits.len().min(2));
+            sign * (h.parse::<i64>().ok()? * 3600 + m.parse::<i64>().unwrap_or(0) * 60)
+        }
+    };
+
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    let days = days_from_civil(year, month, day);
+    Some(days * 86400 + hour * 3600 + minute * 60 + second - offset_secs)
+}
+
+/// 1970-01-01 からの日数 (グレゴリオ暦)
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_index = (month + 9) % 12;
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// 1970-01-01 からの日数を年月日にする (days_from_civil の逆)
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// UNIX 秒を「2024-01-06T22:40:00+09:00」の形にする (offset は UTC からの秒数)
+pub fn format_datetime(epoch: i64, offset_secs: i64) -> String {
+    let local = epoch + offset_secs;
+    let (year, month, day) = civil_from_days(local.div_euclid(86400));
+    let secs = local.rem_euclid(86400);
+    let sign = if offset_secs < 0 { '-' } else { '+' };
+    let offset = offset_secs.abs() / 60;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}{}{:02}:{:02}",
+        year,
+        month,
+        day,
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60,
+        sign,
+        offset / 60,
+        offset % 60
+    )
+}
+
+/// 「Sun, 06 Nov 1994 08:49:37 GMT」を UNIX 秒にする
+pub fn parse_http_date(text: &str) -> Option<i64> {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let (_, rest) = text.split_once(", ")?;
+    let mut parts = rest.split_whitespace();
+    let day = parts.next()?;
+    let month = parts.next()?;
+    let month = MONTHS.iter().position(|m| *m == month)? + 1;
+    let year = parts.next()?;
+    let time = parts.next()?;
+    parse_datetime(&format!("{}-{:02}-{}T{}Z", year, month, day, time))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JST: i64 = 9 * 3600;
+
+    #[test]
+    fn parses_datetime_with_offsets() {
+        assert_eq!(parse_datetime("1970-01-01T00:00:00Z"), Some(0));
+        assert_eq!(
+            parse_datetime("2024-01-06T22:40:00+09:00"),
+            Some(1_704_548_400)
+        );
+        // AtCoder のコンテスト一覧の形式 (空白区切り、コロンなしのオフセット)
+        assert_eq!(
+            parse_datetime("2024-01-06 21:00:00+0900"),
+            Some(1_704_542_400)
+        );
+        assert_eq!(
+            parse_datetime("2024-01-06T13:40:00.123Z"),
+            Some(1_704_548_400)
+        );
+        assert_eq!(
+            parse_datetime("2024-01-06T04:40:00-09:00"),
+            Some(1_704_548_400)
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_datetime() {
+        assert_eq!(parse_datetime(""), None);
+        assert_eq!(parse_datetime("2024-01-06"), None);
+        assert_eq!(parse_datetime("2024-13-01T00:00:00Z"), None);
+        assert_eq!(parse_datetime("2024-01-32T00:00:00Z"), None);
+        assert_eq!(parse_datetime("yesterday at noon"), None);
+    }
+
+    #[test]
+    fn formats_datetime_in_offset() {
+        assert_eq!(format_datetime(0, 0), "1970-01-01T00:00:00+00:00");
+        assert_eq!(
+            format_datetime(1_704_548_400, JST),
+            "2024-01-06T22:40:00+09:00"
+        );
+        assert_eq!(
+            format_datetime(0, -(5 * 3600 + 30 * 60)),
+            "1969-12-31T18:30:00-05:30"
+        );
+        assert_eq!(
+            format_datetime(-310_521_600, 0),
+            "1960-02-29T00:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn format_and_parse_round_trip() {
+        for epoch in [-310_521_600, 0, 951_782_400, 1_709_164_800, 4_102_444_799] {
+            assert_eq!(parse_datetime(&format_datetime(epoch, JST)), Some(epoch));
+        }
+    }
+
+    #[test]
+    fn civil_conversion_round_trips() {
+        for days in -800_000..800_000 {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+        }
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(
+            days_from_civil(2000, 3, 1) - days_from_civil(2000, 2, 28),
+            2
+        );
+        assert_eq!(
+            days_from_civil(1900, 3, 1) - days_from_civil(1900, 2, 28),
+            1
+        );
+    }
+
+    #[test]
+    fn parses_http_date() {
+        assert_eq!(
+            parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"),
+            Some(784_111_777)
+        );
+        assert_eq!(parse_http_date("Sun, 06 Foo 1994 08:49:37 GMT"), None);
+        assert_eq!(parse_http_date("784111777"), None);
+    }
+}
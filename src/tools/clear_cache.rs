@@ -0,0 +1,53 @@
+use super::{Tool, ToolAnnotations, ToolContext};
+use crate::reference;
+
+tool_args! {
+    /// clear_cache の引数
+    pub struct ClearCacheArgs {
+        /// このコンテストのページだけ消す (省略時はすべて)
+        contest_id: Option<String> where { "minLength": 1 },
+    }
+}
+
+/// ページキャッシュを消すツール
+pub struct ClearCache;
+
+impl Tool for ClearCache {
+    type Args = ClearCacheArgs;
+    type Output = String;
+
+    const NAME: &'static str = "clear_cache";
+    const DESCRIPTION: &'static str = "取得した AtCoder のページのキャッシュを消します。contest_id を指定するとそのコンテストのページだけを消します。問題文が更新されたのに古い内容が返るときに使います。";
+    const ANNOTATIONS: ToolAnnotations = ToolAnnotations {
+        title: Some("キャッシュの削除"),
+        read_only: false,
+        destructive: true,
+        idempotent: true,
+        open_world: false,
+    };
+
+    async fn call(&self, args: ClearCacheArgs, ctx: ToolContext) -> anyhow::Result<String> {
+        let cache = ctx.atcoder().cache();
+        let Some(dir) = cache.dir() else {
+            return Ok("キャッシュは無効になっています。".to_string());
+        };
+        // 他のツールと同じく "ABC335" も abc335 として扱う
+        let contest_id = args
+            .contest_id
+            .as_deref()
+            .map(|id| reference::contest_from_args(None, Some(id)))
+            .transpose()?;
+        let cleared = cache.clear(contest_id.as_deref()).await?;
+        let target = match &contest_id {
+            Some(contest_id) => format!("{} の", contest_id),
+            None => String::new(),
+        };
+        Ok(format!(
+            "{}キャッシュを {} 件 ({} KB) 削除しました ({})",
+            target,
+            cleared.entries,
+            cleared.bytes.div_ceil(1024),
+            dir.display()
+        ))
+    }
+}
@@ -0,0 +1,85 @@
+use crate::atcoder::AtCoder;
+use crate::config::Config;
+use crate::protocol::ProtocolVersion;
+use crate::rpc::{Outgoing, notification};
+use serde_json::{Value, json};
+use std::sync::Arc;
+
+/// ツール実行中に使えるサーバー側の機能
+#[derive(Clone)]
+pub struct ToolContext {
+    progress: Option<Progress>,
+    config: Arc<Config>,
+    atcoder: Arc<AtCoder>,
+    protocol: ProtocolVersion,
+}
+
+/// クライアントが `_meta.progressToken` を付けてきたときの進捗通知先
+#[derive(Clone)]
+struct Progress {
+    token: Value,
+    outgoing: Outgoing,
+}
+
+impl ToolContext {
+    /// tools/call の params から context を作る
+    pub fn from_params(
+        params: &Value,
+        outgoing: &Outgoing,
+        config: &Arc<Config>,
+        atcoder: &Arc<AtCoder>,
+        protocol: ProtocolVersion,
+    ) -> Self {
+        let progress = params
+            .get("_meta")
+            .and_then(|meta| meta.get("progressToken"))
+            .filter(|token| token.is_string() || token.is_number())
+            .map(|token| Progress {
+                token: token.clone(),
+                outgoing: outgoing.clone(),
+            });
+        Self {
+            progress,
+            config: Arc::clone(config),
+            atcoder: Arc::clone(atcoder),
+            protocol,
+        }
+    }
+
+    /// initialize で決まったプロトコルバージョン
+    pub fn protocol(&self) -> ProtocolVersion {
+        self.protocol
+    }
+
+    /// サーバーの設定
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// atcoder.jp へのアクセス (キャッシュ付き)
+    pub fn atcoder(&self) -> &AtCoder {
+        &self.atcoder
+    }
+
+    /// notifications/progress を送る
+    /// progressToken が指定されていないリクエストでは何もしない。
+    /// progress は呼び出しごとに増やしていくこと (MCP の仕様)。
+    /// message は 2025-03-26 より前のクライアントには送らない
+    pub fn report_progress(&self, progress: f64, total: Option<f64>, message: impl Into<String>) {
+        let Some(p) = &self.progress else {
+            return;
+        };
+        let mut params = json!({
+            "progressToken": p.token,
+            "progress": progress
+        });
+        if self.protocol.supports_progress_message() {
+            params["message"] = json!(message.into());
+        }
+        if let Some(total) = total {
+            params["total"] = json!(total);
+        }
+        p.outgoing
+            .send(notification("notifications/progress", params));
+    }
+}
@@ -0,0 +1,57 @@
+use super::{Tool, ToolAnnotations, ToolContext, ToolOutput};
+use crate::contest::Contest;
+use crate::reference;
+use crate::schema::SchemaType;
+use serde_json::Value;
+
+tool_args! {
+    /// fetch_contest の引数
+    pub struct FetchContestArgs {
+        /// コンテストID (例: abc335)。problem とどちらか一方
+        contest_id: Option<String> where { "minLength": 1 },
+        /// コンテストや問題の指定 (例: "ABC335"、"ABC335 C"、コンテストや問題ページの URL)
+        problem: Option<String> where { "minLength": 1 },
+        /// ユーザーのレーティング。指定すると、その人にとって Rated かを rated_for_you に入れる
+        rating: Option<u32>,
+    }
+}
+
+/// コンテストの情報を取得するツール
+pub struct FetchContest;
+
+impl Tool for FetchContest {
+    type Args = FetchContestArgs;
+    type Output = Contest;
+
+    const NAME: &'static str = "fetch_contest";
+    const DESCRIPTION: &'static str = "AtCoderのコンテストのトップページから、コンテスト名・開始/終了時刻 (日本時間)・コンテスト時間・Rated 対象・ペナルティ・状態 (upcoming / running / archived / permanent) を取得します。開催中 (running) のコンテストの問題についてはヒントや解法を教えないでください。";
+    const ANNOTATIONS: ToolAnnotations = ToolAnnotations {
+        title: Some("AtCoder コンテスト情報の取得"),
+        ..ToolAnnotations::READ_ONLY_WEB
+    };
+
+    async fn call(&self, args: FetchContestArgs, ctx: ToolContext) -> anyhow::Result<Contest> {
+        let contest_id =
+            reference::contest_from_args(args.problem.as_deref(), args.contest_id.as_deref())?;
+        ctx.report_progress(
+            0.0,
+            Some(1.0),
+            format!("{} のコンテストページを取得中", contest_id),
+        );
+        let result = ctx.atcoder().fetch_contest(&contest_id).await;
+        ctx.report_progress(1.0, Some(1.0), "取得完了");
+        Ok(result?.with_rating(args.rating))
+    }
+}
+
+/// テキストは Markdown の箇条書き、structuredContent は Contest
+impl ToolOutput for Contest {
+    fn output_schema() -> Option<Value> {
+        Some(Contest::schema())
+    }
+
+    fn into_content(self) -> (String, Option<Value>) {
+        let text = self.to_markdown();
+        (text, serde_json::to_value(&self).ok())
+    }
+}
@@ -0,0 +1,49 @@
+use super::{ProblemArgs, Tool, ToolAnnotations, ToolContext, ToolOutput};
+use crate::editorial::EditorialIndex;
+use crate::schema::SchemaType;
+use serde_json::Value;
+
+/// 解説ページ(一覧)を取得するツール
+pub struct FetchEditorial;
+
+impl Tool for FetchEditorial {
+    type Args = ProblemArgs;
+    type Output = EditorialIndex;
+
+    const NAME: &'static str = "fetch_editorial";
+    const DESCRIPTION: &'static str = "AtCoderの解説一覧を取得します。公式解説・ユーザー解説ごとに、著者・言語・URL が分かります。本文は fetch_editorial_article に URL を渡して取得します。問題は problem (例: \"ABC335 C\" や問題ページの URL) か、contest_id と problem_id で指定します。開催中のコンテストや、終わったか確かめられないコンテストの解説は取得しません。";
+    const ANNOTATIONS: ToolAnnotations = ToolAnnotations {
+        title: Some("AtCoder 解説一覧の取得"),
+        ..ToolAnnotations::READ_ONLY_WEB
+    };
+
+    async fn call(&self, args: ProblemArgs, ctx: ToolContext) -> anyhow::Result<EditorialIndex> {
+        let problem = args.reference()?;
+        ctx.atcoder()
+            .ensure_contest_finished(&problem.contest_id)
+            .await?;
+        ctx.report_progress(
+            0.0,
+            Some(1.0),
+            format!("{} の解説ページを取得中", problem.problem_id),
+        );
+        let result = ctx
+            .atcoder()
+            .fetch_editorial(&problem.contest_id, &problem.problem_id)
+            .await;
+        ctx.report_progress(1.0, Some(1.0), "取得完了");
+        result
+    }
+}
+
+/// テキストは Markdown の一覧、structuredContent は EditorialIndex
+impl ToolOutput for EditorialIndex {
+    fn output_schema() -> Option<Value> {
+        Some(EditorialIndex::schema())
+    }
+
+    fn into_content(self) -> (String, Option<Value>) {
+        let text = self.to_markdown();
+        (text, serde_json::to_value(&self).ok())
+    }
+}
@@ -0,0 +1,53 @@
+use super::{Tool, ToolAnnotations, ToolContext, ToolOutput};
+use crate::editorial::{self, EditorialArticle};
+use crate::schema::SchemaType;
+use serde_json::Value;
+
+tool_args! {
+    /// fetch_editorial_article の引数
+    pub struct FetchEditorialArticleArgs {
+        /// 解説の URL (例: https://atcoder.jp/contests/abc335/editorial/9041)。fetch_editorial の結果の url をそのまま渡す
+        url: String where { "minLength": 1 },
+    }
+}
+
+/// 個別の解説ページを取得するツール
+pub struct FetchEditorialArticle;
+
+impl Tool for FetchEditorialArticle {
+    type Args = FetchEditorialArticleArgs;
+    type Output = EditorialArticle;
+
+    const NAME: &'static str = "fetch_editorial_article";
+    const DESCRIPTION: &'static str = "AtCoderの解説ページの本文を、数式とコードを保ったまま Markdown で取得します。url には fetch_editorial で得た解説の URL を渡します (atcoder.jp の外の解説には対応していません)。解説全文を返すので、ユーザーが解説そのものを読みたいときだけ使い、ヒントが欲しいときは get_hint を使ってください。開催中のコンテストや、終わったか確かめられないコンテストの解説は取得しません。";
+    const ANNOTATIONS: ToolAnnotations = ToolAnnotations {
+        title: Some("AtCoder 解説本文の取得"),
+        ..ToolAnnotations::READ_ONLY_WEB
+    };
+
+    async fn call(
+        &self,
+        args: FetchEditorialArticleArgs,
+        ctx: ToolContext,
+    ) -> anyhow::Result<EditorialArticle> {
+        // 開催中のコンテストの解説は出さない (get_hint と同じ確認)
+        let contest_id = editorial::article_contest_id(&args.url)?;
+        ctx.atcoder().ensure_contest_finished(&contest_id).await?;
+        ctx.report_progress(0.0, Some(1.0), "解説ページを取得中");
+        let result = ctx.atcoder().fetch_editorial_article(&args.url).await;
+        ctx.report_progress(1.0, Some(1.0), "取得完了");
+        result
+    }
+}
+
+/// テキストはタイトル付きの Markdown、structuredContent は EditorialArticle
+impl ToolOutput for EditorialArticle {
+    fn output_schema() -> Option<Value> {
+        Some(EditorialArticle::schema())
+    }
+
+    fn into_content(self) -> (String, Option<Value>) {
+        let text = self.to_markdown();
+        (text, serde_json::to_value(&self).ok())
+    }
+}
@@ -0,0 +1,58 @@
+use super::{ProblemArgs, Tool, ToolAnnotations, ToolContext, ToolOutput};
+use crate::atcoder::Lang;
+use crate::problem::{Problem, ProblemPage};
+use crate::schema::SchemaType;
+use serde_json::Value;
+
+tool_args! {
+    /// fetch_problem の引数
+    pub struct FetchProblemArgs {
+        #[flatten]
+        problem: ProblemArgs,
+        /// 問題文の言語 (ja / en / both)。省略時はサーバーの既定値。英語版が無い問題は日本語で返す
+        lang: Option<Lang>,
+    }
+}
+
+/// 問題文を取得するツール
+pub struct FetchProblem;
+
+impl Tool for FetchProblem {
+    type Args = FetchProblemArgs;
+    type Output = ProblemPage;
+
+    const NAME: &'static str = "fetch_problem";
+    const DESCRIPTION: &'static str = "AtCoderの問題文を Markdown で取得します。問題は problem (例: \"ABC335 C\"、\"abc335c\"、問題ページの URL) か、contest_id (例: abc335) と problem_id (例: abc335_a、または問題の記号 A) で指定します。lang で日本語 (ja)・英語 (en)・両方 (both) を選べます。";
+    const ANNOTATIONS: ToolAnnotations = ToolAnnotations {
+        title: Some("AtCoder 問題文の取得"),
+        ..ToolAnnotations::READ_ONLY_WEB
+    };
+
+    async fn call(&self, args: FetchProblemArgs, ctx: ToolContext) -> anyhow::Result<ProblemPage> {
+        let problem = args.problem.reference()?;
+        ctx.report_progress(
+            0.0,
+            Some(1.0),
+            format!("{} の問題ページを取得中", problem.problem_id),
+        );
+        let lang = args.lang.unwrap_or(ctx.config().default_lang);
+        let result = ctx
+            .atcoder()
+            .fetch_problem(&problem.contest_id, &problem.problem_id, lang)
+            .await;
+        ctx.report_progress(1.0, Some(1.0), "取得完了");
+        result
+    }
+}
+
+/// テキストは Markdown の問題文、structuredContent はサンプルや制限を分解した Problem
+impl ToolOutput for ProblemPage {
+    fn output_schema() -> Option<Value> {
+        Some(Problem::schema())
+    }
+
+    fn into_content(self) -> (String, Option<Value>) {
+        let structured = serde_json::to_value(&self.problem).ok();
+        (self.markdown, structured)
+    }
+}
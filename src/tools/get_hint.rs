@@ -0,0 +1,248 @@
+use super::{ProblemArgs, Tool, ToolAnnotations, ToolContext, ToolOutput};
+use crate::hint::{self, MAX_LEVEL};
+use crate::reference::ProblemRef;
+use crate::schema::SchemaType;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+tool_args! {
+    /// get_hint の引数
+    pub struct GetHintArgs {
+        #[flatten]
+        problem: ProblemArgs,
+        /// ヒントの段階 (1: 手法の分類, 2: 鍵になる観察, 3: 解法の流れ (見出しと各節の最初の一文), 4: 解説全文)。まだ開けていない段階は1つずつしか進めない
+        level: u32 where { "minimum": 1, "maximum": 4 },
+        /// レベル 4 (解説全文) を開けるときに true にする。ユーザーが解説全文を見たいとはっきり頼んだときだけ指定する
+        reveal_full: Option<bool>,
+    }
+}
+
+tool_output! {
+    /// get_hint の結果
+    pub struct Hint {
+        /// コンテストID
+        contest_id: String,
+        /// 問題ID
+        problem_id: String,
+        /// 返したヒントの段階
+        level: u32,
+        /// 段階の名前 (例: 手法の分類)
+        level_name: String,
+        /// この問題でこれまでに開けた一番上の段階
+        unlocked_level: u32,
+        /// ヒントの本文 (Markdown)
+        hint: String,
+        /// もとにした解説の URL
+        source_url: String,
+    }
+}
+
+/// 解説をもとに段階的なヒントを出すツール
+///
+/// 問題ごとにどの段階まで開けたかをセッションの間覚えておき、
+/// 開けた段階の次より先は返さない (レベル1を頼まれたのにレベル4を見せてしまわないように)。
+/// 解説全文のレベル4は、順に開けてきても `reveal_full: true` が無ければ返さない。
+#[derive(Default)]
+pub struct GetHint {
+    /// (contest_id, problem_id) ごとの開けた段階
+    unlocked: Mutex<HashMap<(String, String), u32>>,
+}
+
+impl GetHint {
+    fn unlocked_level(&self, key: &(String, String)) -> u32 {
+        self.unlocked.lock().unwrap().get(key).copied().unwrap_or(0)
+    }
+
+    fn unlock(&self, key: (String, String), level: u32) -> u32 {
+        let mut unlocked = self.unlocked.lock().unwrap();
+        let entry = unlocked.entry(key).or_insert(0);
+        *entry = (*entry).max(level);
+        *entry
+    }
+}
+
+impl Tool for GetHint {
+    type Args = GetHintArgs;
+    type Output = Hint;
+
+    const NAME: &'static str = "get_hint";
+    const DESCRIPTION: &'static str = "AtCoderの公式解説をもとに、ネタバレの度合いを段階的に上げたヒントを返します。level は 1 (手法の分類)・2 (鍵になる観察)・3 (解法の流れ: 見出しと各節の最初の一文)・4 (解説全文)。問題ごとにどこまで開けたかを覚えていて、まだ開けていない段階は1つずつしか進めません。level 4 はさらに reveal_full: true が必要で、ユーザーが解説全文を見たいとはっきり頼んだときだけ指定してください。この段階の制限は get_hint の中だけのもので、fetch_editorial_article などの解説全文の取得は止めません。開催中のコンテストの問題や、コンテストが終わったか確かめられない問題にはヒントを出しません。ユーザーが頼んだ段階より上のヒントを勝手に取得しないでください。";
+    const ANNOTATIONS: ToolAnnotations = ToolAnnotations {
+        title: Some("段階的なヒント"),
+        // 開けた段階を覚えるので、同じ引数でも呼ぶ前後で結果 (unlocked_level) が変わりうる
+        idempotent: false,
+        ..ToolAnnotations::READ_ONLY_WEB
+    };
+
+    async fn call(&self, args: GetHintArgs, ctx: ToolContext) -> anyhow::Result<Hint> {
+        if args.level == MAX_LEVEL && args.reveal_full != Some(true) {
+            anyhow::bail!(
+                "Hint level {} is the full editorial. Set reveal_full: true only if the user explicitly asked to see the full editorial.",
+                MAX_LEVEL
+            );
+        }
+        let atcoder = ctx.atcoder();
+        let ProblemRef {
+            contest_id,
+            problem_id,
+        } = args.problem.reference()?;
+        // 開催中のコンテストの問題にはヒントを出さない (状態が分からないときも出さない)
+        atcoder.ensure_contest_finished(&contest_id).await?;
+        // 記号・URL・問題IDのどれで呼ばれても同じ問題として数える
+        let problem_id = atcoder.resolve_problem_id(&contest_id, &problem_id).await?;
+        let key = (contest_id.clone(), problem_id.clone());
+        let unlocked = self.unlocked_level(&key);
+        if args.level > unlocked + 1 {
+            anyhow::bail!(
+                "Hint level {} is locked for {}. Level {} has been unlocked so far; request level {} first.",
+                args.level,
+                problem_id,
+                unlocked,
+                unlocked + 1
+            );
+        }
+
+        ctx.report_progress(0.0, Some(2.0), "解説一覧を取得中");
+        let index = atcoder.fetch_editorial(&contest_id, &problem_id).await?;
+        let Some(entry) = index.official(ctx.config().default_lang) else {
+            anyhow::bail!("No official editorial for {} is available yet.", problem_id)
+        };
+        ctx.report_progress(1.0, Some(2.0), "解説を取得中");
+        let article = atcoder.fetch_editorial_article(&entry.url).await?;
+        ctx.report_progress(2.0, Some(2.0), "取得完了");
+
+        let body = match args.level {
+            1 => {
+                let techniques = hint::techniques(&article.markdown);
+                if techniques.is_empty() {
+                    "解説からは手法の分類を判断できませんでした。次の段階 (鍵になる観察) に進んでください。".to_string()
+                } else {
+                    techniques
+                        .iter()
+                        .map(|t| format!("- {}", t))
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                }
+            }
+            2 => hint::key_observation(&article.markdown)
+                .unwrap_or_else(|| "解説から観察を取り出せませんでした。".to_string()),
+            3 => hint::outline(&article.markdown)
+                .unwrap_or_else(|| "解説から解法の流れを取り出せませんでした。".to_string()),
+            _ => article.to_markdown(),
+        };
+
+        let unlocked_level = self.unlock(key, args.level);
+        Ok(Hint {
+            contest_id,
+            problem_id,
+            level: args.level,
+            level_name: hint::level_name(args.level).to_string(),
+            unlocked_level,
+            hint: body,
+            source_url: article.url,
+        })
+    }
+}
+
+/// テキストはヒントの本文と次の段階の案内、structuredContent は Hint
+impl ToolOutput for Hint {
+    fn output_schema() -> Option<Value> {
+        Some(Hint::schema())
+    }
+
+    fn into_content(self) -> (String, Option<Value>) {
+        let mut text = format!(
+            "## {} のヒント (レベル {}: {})\n\n{}",
+            self.problem_id, self.level, self.level_name, self.hint
+        );
+        if self.unlocked_level < MAX_LEVEL {
+            let next = self.unlocked_level + 1;
+            let how = if next == MAX_LEVEL {
+                "ユーザーが全文を見たいと頼んだときだけ reveal_full: true を付けて取得してください"
+            } else {
+                "ユーザーが望んだときだけ取得してください"
+            };
+            text.push_str(&format!(
+                "\n\n(次に開けられるのはレベル {}: {} です。{})",
+                next,
+                hint::level_name(next),
+                how
+            ));
+        }
+        (text, serde_json::to_value(&self).ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::atcoder::tests::{PAGES, offline};
+    use crate::config::Config;
+    use crate::protocol::ProtocolVersion;
+    use crate::rpc::Outgoing;
+    use crate::schema::parse_args;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    async fn get_hint(tool: &GetHint, ctx: &ToolContext, args: Value) -> anyhow::Result<Hint> {
+        tool.call(parse_args(args).unwrap(), ctx.clone()).await
+    }
+
+    #[tokio::test]
+    async fn full_editorial_needs_reveal_full() {
+        let atcoder = Arc::new(offline("get-hint", PAGES).await);
+        let (outgoing, _) = Outgoing::channel();
+        let ctx = ToolContext::from_params(
+            &json!({}),
+            &outgoing,
+            &Arc::new(Config::default()),
+            &atcoder,
+            ProtocolVersion::LATEST,
+        );
+        let tool = GetHint::default();
+
+        let error = get_hint(&tool, &ctx, json!({ "problem": "ABC335 C", "level": 2 }))
+            .await
+            .unwrap_err();
+        assert!(
+            error.to_string().starts_with("Hint level 2 is locked"),
+            "{}",
+            error
+        );
+        for level in 1..=3 {
+            let hint = get_hint(
+                &tool,
+                &ctx,
+                json!({ "problem": "ABC335 C", "level": level }),
+            )
+            .await
+            .unwrap();
+            assert_eq!((hint.level, hint.unlocked_level), (level, level));
+        }
+
+        // 順に開けてきても、reveal_full が無ければ全文は出さない
+        for args in [
+            json!({ "problem": "abc335_c", "level": 4 }),
+            json!({ "problem": "abc335_c", "level": 4, "reveal_full": false }),
+        ] {
+            let error = get_hint(&tool, &ctx, args).await.unwrap_err();
+            assert!(error.to_string().contains("reveal_full: true"), "{}", error);
+        }
+        let hint = get_hint(
+            &tool,
+            &ctx,
+            json!({ "problem": "abc335_c", "level": 4, "reveal_full": true }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(hint.unlocked_level, 4);
+        assert!(hint.hint.starts_with("# C - Loong Tracking 解説"));
+        assert_eq!(
+            hint.source_url,
+            "https://atcoder.jp/contests/abc335/editorial/9041"
+        );
+
+        let _ = std::fs::remove_dir_all(atcoder.cache().dir().unwrap());
+    }
+}
@@ -0,0 +1,55 @@
+use super::{Tool, ToolAnnotations, ToolContext, ToolOutput};
+use crate::contest::ContestTasks;
+use crate::reference;
+use crate::schema::SchemaType;
+use serde_json::Value;
+
+tool_args! {
+    /// list_contest_tasks の引数
+    pub struct ListContestTasksArgs {
+        /// コンテストID (例: abc335)。problem とどちらか一方
+        contest_id: Option<String> where { "minLength": 1 },
+        /// コンテストや問題の指定 (例: "ABC335"、"ABC335 C"、コンテストや問題ページの URL)
+        problem: Option<String> where { "minLength": 1 },
+    }
+}
+
+/// コンテストの問題一覧を取得するツール
+pub struct ListContestTasks;
+
+impl Tool for ListContestTasks {
+    type Args = ListContestTasksArgs;
+    type Output = ContestTasks;
+
+    const NAME: &'static str = "list_contest_tasks";
+    const DESCRIPTION: &'static str = "AtCoderのコンテストの問題一覧を取得します。問題ごとに記号 (A など)・問題名・問題ID・実行時間制限・メモリ制限・URL が分かります。問題IDはコンテストIDから推測できないことがある (abc042 の問題が arc058_a など) ので、分からないときはこれで調べてください。";
+    const ANNOTATIONS: ToolAnnotations = ToolAnnotations {
+        title: Some("AtCoder 問題一覧の取得"),
+        ..ToolAnnotations::READ_ONLY_WEB
+    };
+
+    async fn call(
+        &self,
+        args: ListContestTasksArgs,
+        ctx: ToolContext,
+    ) -> anyhow::Result<ContestTasks> {
+        let contest_id =
+            reference::contest_from_args(args.problem.as_deref(), args.contest_id.as_deref())?;
+        ctx.report_progress(0.0, Some(1.0), format!("{} の問題一覧を取得中", contest_id));
+        let result = ctx.atcoder().fetch_tasks(&contest_id).await;
+        ctx.report_progress(1.0, Some(1.0), "取得完了");
+        result
+    }
+}
+
+/// テキストは Markdown の表、structuredContent は ContestTasks
+impl ToolOutput for ContestTasks {
+    fn output_schema() -> Option<Value> {
+        Some(ContestTasks::schema())
+    }
+
+    fn into_content(self) -> (String, Option<Value>) {
+        let text = self.to_markdown();
+        (text, serde_json::to_value(&self).ok())
+    }
+}
@@ -0,0 +1,59 @@
+use super::{Tool, ToolAnnotations, ToolContext, ToolOutput};
+use crate::contest::{ContestSchedule, ContestStatus};
+use crate::schema::SchemaType;
+use serde_json::Value;
+
+tool_args! {
+    /// list_contests の引数
+    pub struct ListContestsArgs {
+        /// この状態のコンテストだけを返す (upcoming / running / archived / permanent / unknown)。省略時はすべて
+        status: Option<ContestStatus>,
+        /// ユーザーのレーティング。指定すると、コンテストごとに Rated かを rated_for_you に入れる
+        rating: Option<u32>,
+    }
+}
+
+/// コンテストの予定を取得するツール
+pub struct ListContests;
+
+impl Tool for ListContests {
+    type Args = ListContestsArgs;
+    type Output = ContestSchedule;
+
+    const NAME: &'static str = "list_contests";
+    const DESCRIPTION: &'static str = "AtCoderのコンテスト一覧 (開催中・開始前・最近終わった・常設) を取得します。コンテストごとに開始時刻 (日本時間)・コンテスト時間・Rated 対象・状態が分かります。rating を渡すと、その人にとって Rated かも分かります。「今週末のコンテスト」のような質問には、結果の now と開始時刻を比べて答えてください。";
+    const ANNOTATIONS: ToolAnnotations = ToolAnnotations {
+        title: Some("AtCoder コンテスト一覧の取得"),
+        ..ToolAnnotations::READ_ONLY_WEB
+    };
+
+    async fn call(
+        &self,
+        args: ListContestsArgs,
+        ctx: ToolContext,
+    ) -> anyhow::Result<ContestSchedule> {
+        ctx.report_progress(0.0, Some(1.0), "コンテスト一覧を取得中");
+        let result = ctx.atcoder().fetch_contests().await;
+        ctx.report_progress(1.0, Some(1.0), "取得完了");
+        let mut schedule = result?;
+        schedule.contests = schedule
+            .contests
+            .into_iter()
+            .filter(|c| args.status.is_none_or(|status| c.status == status))
+            .map(|c| c.with_rating(args.rating))
+            .collect();
+        Ok(schedule)
+    }
+}
+
+/// テキストは状態ごとの Markdown の表、structuredContent は ContestSchedule
+impl ToolOutput for ContestSchedule {
+    fn output_schema() -> Option<Value> {
+        Some(ContestSchedule::schema())
+    }
+
+    fn into_content(self) -> (String, Option<Value>) {
+        let text = self.to_markdown();
+        (text, serde_json::to_value(&self).ok())
+    }
+}
@@ -0,0 +1,290 @@
+//! MCP ツールの定義とレジストリ
+//!
+//! ツールは `Tool` トレイトを実装して `registry()` に登録するだけでよい。
+//! tools/list と tools/call はレジストリから自動で組み立てられる。
+
+mod clear_cache;
+mod context;
+mod fetch_contest;
+mod fetch_editorial;
+mod fetch_editorial_article;
+mod fetch_problem;
+mod get_hint;
+mod list_contest_tasks;
+mod list_contests;
+mod run_interactive;
+mod run_samples;
+mod stress_test;
+
+use crate::atcoder::AtCoder;
+use crate::config::Config;
+use crate::protocol::ProtocolVersion;
+use crate::reference::{self, ProblemRef};
+use crate::rpc::{Outgoing, RpcError};
+use crate::schema::{ToolArgs, parse_args};
+use serde_json::{Value, json};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+pub use clear_cache::ClearCache;
+pub use context::ToolContext;
+pub use fetch_contest::FetchContest;
+pub use fetch_editorial::FetchEditorial;
+pub use fetch_editorial_article::FetchEditorialArticle;
+pub use fetch_problem::FetchProblem;
+pub use get_hint::GetHint;
+pub use list_contest_tasks::ListContestTasks;
+pub use list_contests::ListContests;
+pub use run_interactive::RunInteractive;
+pub use run_samples::RunSamples;
+pub use stress_test::StressTest;
+
+tool_args! {
+    /// 問題を指定する引数
+    pub struct ProblemArgs {
+        /// 問題 (例: "ABC335 C", "abc335c", "abc335_c", 問題ページの URL)。contest_id と problem_id の代わりに使える
+        problem: Option<String> where { "minLength": 1 },
+        /// コンテストID (例: abc335)。problem を使わないときは必須
+        contest_id: Option<String> where { "minLength": 1 },
+        /// 問題ID (例: abc335_a) または問題の記号 (例: A)。problem を使わないときは必須
+        problem_id: Option<String> where { "minLength": 1 },
+    }
+}
+
+impl ProblemArgs {
+    /// problem / contest_id / problem_id から問題を決める
+    pub fn reference(&self) -> anyhow::Result<ProblemRef> {
+        reference::from_args(
+            self.problem.as_deref(),
+            self.contest_id.as_deref(),
+            self.problem_id.as_deref(),
+        )
+    }
+
+    /// 問題の指定が任意のツール用 (何も指定されなければ None)
+    pub fn optional_reference(&self) -> anyhow::Result<Option<ProblemRef>> {
+        reference::optional_from_args(
+            self.problem.as_deref(),
+            self.contest_id.as_deref(),
+            self.problem_id.as_deref(),
+        )
+    }
+}
+
+type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// 1つのツールの定義 (名前・説明・引数・処理をまとめて持つ)
+pub trait Tool: Send + Sync + 'static {
+    /// 引数の型 (inputSchema もここから作られる)
+    type Args: ToolArgs + Send;
+
+    /// ツール名
+    const NAME: &'static str;
+
+    /// LLM 向けの説明文
+    const DESCRIPTION: &'static str;
+
+    /// ツールの性質 (2025-03-26 以降のクライアントにだけ annotations として渡す)
+    const ANNOTATIONS: ToolAnnotations = ToolAnnotations::READ_ONLY_WEB;
+
+    /// 結果の型 (構造化出力を持つなら outputSchema もここから作られる)
+    type Output: ToolOutput;
+
+    /// ツールの本体。Err はそのまま isError: true の結果になる
+    /// 時間のかかる処理は `ctx.report_progress` で進捗を通知できる
+    fn call(
+        &self,
+        args: Self::Args,
+        ctx: ToolContext,
+    ) -> impl Future<Output = anyhow::Result<Self::Output>> + Send;
+}
+
+/// ツールの結果として返せる型
+pub trait ToolOutput: Send {
+    /// outputSchema (構造化出力を持たないなら None)
+    fn output_schema() -> Option<Value> {
+        None
+    }
+
+    /// content に入れるテキストと structuredContent
+    fn into_content(self) -> (String, Option<Value>);
+}
+
+/// テキストだけを返すツール用
+impl ToolOutput for String {
+    fn into_content(self) -> (String, Option<Value>) {
+        (self, None)
+    }
+}
+
+/// ツールの振る舞いについてのヒント (MCP の ToolAnnotations)
+pub struct ToolAnnotations {
+    /// 表示用の名前
+    pub title: Option<&'static str>,
+    /// 外部の状態を書き換えない
+    pub read_only: bool,
+    /// 書き換えが破壊的 (read_only でないときだけ意味がある)
+    pub destructive: bool,
+    /// 同じ引数で何度呼んでも結果が変わらない
+    pub idempotent: bool,
+    /// atcoder.jp など外の世界とやり取りする
+    pub open_world: bool,
+}
+
+impl ToolAnnotations {
+    /// AtCoder のページを読むだけのツール
+    pub const READ_ONLY_WEB: ToolAnnotations = ToolAnnotations {
+        title: None,
+        read_only: true,
+        destructive: false,
+        idempotent: true,
+        open_world: true,
+    };
+
+    fn to_json(&self) -> Value {
+        let mut annotations = json!({
+            "readOnlyHint": self.read_only,
+            "idempotentHint": self.idempotent,
+            "openWorldHint": self.open_world
+        });
+        if !self.read_only {
+            annotations["destructiveHint"] = json!(self.destructive);
+        }
+        if let Some(title) = self.title {
+            annotations["title"] = json!(title);
+        }
+        annotations
+    }
+}
+
+/// レジストリに入れるための型消去版
+trait DynTool: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn definition(&self, protocol: ProtocolVersion) -> Value;
+    fn call_json(&self, args: Value, ctx: ToolContext) -> BoxFuture<'_, Result<Value, RpcError>>;
+}
+
+impl<T: Tool> DynTool for T {
+    fn name(&self) -> &'static str {
+        T::NAME
+    }
+
+    fn definition(&self, protocol: ProtocolVersion) -> Value {
+        let mut definition = json!({
+            "name": T::NAME,
+            "description": T::DESCRIPTION,
+            "inputSchema": T::Args::input_schema()
+        });
+        if protocol.supports_tool_annotations() {
+            definition["annotations"] = T::ANNOTATIONS.to_json();
+        }
+        if protocol.supports_structured_output()
+            && let Some(schema) = T::Output::output_schema()
+        {
+            definition["outputSchema"] = schema;
+        }
+        definition
+    }
+
+    fn call_json(&self, args: Value, ctx: ToolContext) -> BoxFuture<'_, Result<Value, RpcError>> {
+        Box::pin(async move {
+            let args = parse_args::<T::Args>(args)?;
+            let protocol = ctx.protocol();
+            Ok(tool_result(self.call(args, ctx).await, protocol))
+        })
+    }
+}
+
+/// ツールの実行結果を MCP の content 形式に包む
+/// 失敗した場合は isError: true を付けてクライアント(LLM)に返す
+fn tool_result<O: ToolOutput>(result: anyhow::Result<O>, protocol: ProtocolVersion) -> Value {
+    match result {
+        Ok(output) => {
+            let (text, structured) = output.into_content();
+            let mut result = json!({
+                "content": [{ "type": "text", "text": text }]
+            });
+            if protocol.supports_structured_output()
+                && let Some(structured) = structured
+            {
+                result["structuredContent"] = structured;
+            }
+            result
+        }
+        Err(e) => json!({
+            "content": [{ "type": "text", "text": format!("Error: {}", e) }],
+            "isError": true
+        }),
+    }
+}
+
+/// 登録済みツールの一覧
+#[derive(Default)]
+pub struct ToolRegistry {
+    tools: Vec<Box<dyn DynTool>>,
+}
+
+impl ToolRegistry {
+    pub fn register<T: Tool>(&mut self, tool: T) -> &mut Self {
+        debug_assert!(
+            self.tools.iter().all(|t| t.name() != T::NAME),
+            "tool {} is registered twice",
+            T::NAME
+        );
+        self.tools.push(Box::new(tool));
+        self
+    }
+
+    /// tools/list の結果 (ネゴシエーションしたバージョンで載せる項目が変わる)
+    pub fn list(&self, protocol: ProtocolVersion) -> Value {
+        let tools: Vec<Value> = self.tools.iter().map(|t| t.definition(protocol)).collect();
+        json!({ "tools": tools })
+    }
+
+    /// tools/call の処理
+    /// 進捗通知は `outgoing` 経由で、レスポンスより先にクライアントへ送られる
+    pub async fn call(
+        &self,
+        params: Option<Value>,
+        outgoing: &Outgoing,
+        config: &Arc<Config>,
+        atcoder: &Arc<AtCoder>,
+        protocol: ProtocolVersion,
+    ) -> Result<Value, RpcError> {
+        let params = params.ok_or_else(|| RpcError::invalid_params("Missing params"))?;
+        let tool_name = params
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or_else(|| RpcError::invalid_params("Missing tool name"))?;
+        let args = params.get("arguments").cloned().unwrap_or(Value::Null);
+
+        // 定義されていないツールが呼ばれた場合
+        let tool = self
+            .tools
+            .iter()
+            .find(|t| t.name() == tool_name)
+            .ok_or_else(|| RpcError::invalid_params(format!("Unknown tool: {}", tool_name)))?;
+
+        let ctx = ToolContext::from_params(&params, outgoing, config, atcoder, protocol);
+        tool.call_json(args, ctx).await
+    }
+}
+
+/// このサーバーが提供するツール一覧
+pub fn registry() -> ToolRegistry {
+    let mut registry = ToolRegistry::default();
+    registry
+        .register(ListContests)
+        .register(FetchContest)
+        .register(ListContestTasks)
+        .register(FetchProblem)
+        .register(FetchEditorial)
+        .register(FetchEditorialArticle)
+        .register(GetHint::default())
+        .register(RunSamples)
+        .register(RunInteractive)
+        .register(StressTest)
+        .register(ClearCache);
+    registry
+}
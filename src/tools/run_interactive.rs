@@ -0,0 +1,277 @@
+use super::run_samples::{
+    CHECKER_TIME_LIMIT_MS, build_helper, find_language, read_source, truncate,
+};
+use super::{ProblemArgs, Tool, ToolAnnotations, ToolContext, ToolOutput};
+use crate::atcoder::Lang;
+use crate::runner::{self, Build, Limits, QueryLimit, Verdict, Workspace};
+use crate::schema::SchemaType;
+use anyhow::Context;
+use serde_json::Value;
+
+tool_args! {
+    /// run_interactive の引数
+    pub struct RunInteractiveArgs {
+        #[flatten]
+        problem: ProblemArgs,
+        /// 解答の言語 (設定の languages のキー)
+        language: String where { "minLength": 1 },
+        /// 解答のソースコード (source_path とどちらか一方)
+        source: Option<String>,
+        /// 解答のソースファイルのパス (source とどちらか一方)
+        source_path: Option<String>,
+        /// 判定プログラムのソースコード。標準入出力で解答とやり取りし、終了コード 0 で AC、1 で WA
+        judge: Option<String>,
+        /// 判定プログラムのソースファイルのパス (judge とどちらか一方)
+        judge_path: Option<String>,
+        /// 判定プログラムの言語 (省略時は language と同じ)
+        judge_language: Option<String>,
+        /// テストケース。1つずつファイルに書き出し、そのパスを判定プログラムの第1引数に渡す (省略時は引数なしで1回だけ実行)
+        cases: Option<Vec<String>>,
+        /// クエリ数の上限 (超えたら WA)
+        max_queries: Option<u64>,
+        /// この文字列で始まる行をクエリとして数える (既定は "?"。空文字列なら解答が送った全行)
+        query_prefix: Option<String>,
+    }
+}
+
+tool_output! {
+    /// やり取りの1行
+    pub struct TranscriptLine {
+        /// 送り手 (solution / judge)
+        from: String,
+        /// 送られた行
+        line: String,
+    }
+}
+
+tool_output! {
+    /// テストケース1つ分の結果
+    pub struct InteractiveResult {
+        /// テストケース番号 (1始まり)
+        case: u32,
+        /// 判定 (AC / WA / RE / TLE / MLE / OLE / JE)
+        verdict: String,
+        /// 実行時間 (ミリ秒)
+        time_ms: u64,
+        /// 解答の最大メモリ使用量 (KB, 計測できたとき)
+        memory_kb: Option<u64>,
+        /// 解答が送ったクエリの数
+        queries: u64,
+        /// 判定プログラムのメッセージなど
+        message: Option<String>,
+        /// 解答の標準エラー出力 (空でなければ)
+        stderr: Option<String>,
+        /// やり取りの記録
+        transcript: Vec<TranscriptLine>,
+        /// 記録が長すぎて途中で打ち切ったか
+        transcript_truncated: bool,
+    }
+}
+
+tool_output! {
+    /// run_interactive の結果
+    pub struct InteractiveReport {
+        /// 問題ID
+        problem_id: String,
+        /// 言語
+        language: String,
+        /// 実行時間制限 (ミリ秒)
+        time_limit_ms: u64,
+        /// メモリ制限 (MB)
+        memory_limit_mb: Option<u64>,
+        /// コンパイルエラーの内容 (解答のコンパイルに失敗したとき)
+        compile_error: Option<String>,
+        /// AC だったテストケースの数
+        passed: u32,
+        /// テストケースの総数
+        total: u32,
+        /// テストケースごとの結果
+        results: Vec<InteractiveResult>,
+    }
+}
+
+/// インタラクティブ問題を判定プログラムとつないでテストするツール
+pub struct RunInteractive;
+
+impl Tool for RunInteractive {
+    type Args = RunInteractiveArgs;
+    type Output = InteractiveReport;
+
+    const NAME: &'static str = "run_interactive";
+    const DESCRIPTION: &'static str = "インタラクティブ問題の解答を、判定プログラム (judge) と標準入出力をつないで実行します。実行時間・メモリ・クエリ数を制限し、やり取りの記録を返します。judge は cases の各テストケースのファイルパスを第1引数で受け取り、終了コード 0 で AC、1 で WA を表します。";
+    const ANNOTATIONS: ToolAnnotations = ToolAnnotations {
+        title: Some("インタラクティブ問題のテスト"),
+        read_only: false,
+        destructive: false,
+        idempotent: true,
+        open_world: true,
+    };
+
+    async fn call(
+        &self,
+        args: RunInteractiveArgs,
+        ctx: ToolContext,
+    ) -> anyhow::Result<InteractiveReport> {
+        let source = read_source(args.source.as_ref(), args.source_path.as_ref(), "source").await?;
+        let language = find_language(ctx.config(), &args.language)?;
+        let problem = args.problem.reference()?;
+
+        // 制限を知るために問題ページを読む
+        let page = ctx
+            .atcoder()
+            .fetch_problem(&problem.contest_id, &problem.problem_id, Lang::Ja)
+            .await?;
+        let limits = Limits::for_problem(
+            page.problem.time_limit_ms,
+            page.problem.memory_limit_mb,
+            &ctx.config().sandbox,
+        );
+        let judge_limits =
+            Limits::for_problem(Some(CHECKER_TIME_LIMIT_MS), None, &ctx.config().sandbox);
+        let query_limit = QueryLimit::new(args.max_queries, args.query_prefix.clone());
+
+        // テストケースが無ければ、判定プログラムに引数を渡さず1回だけ動かす
+        let cases: Vec<Option<&String>> = match &args.cases {
+            Some(cases) if !cases.is_empty() => cases.iter().map(Some).collect(),
+            _ => vec![None],
+        };
+        let total = cases.len() as u32;
+        let steps = f64::from(total + 1);
+        let mut report = InteractiveReport {
+            problem_id: page.problem.problem_id.clone(),
+            language: args.language.clone(),
+            time_limit_ms: limits.time.as_millis() as u64,
+            memory_limit_mb: limits.memory_bytes.map(|b| b / 1024 / 1024),
+            compile_error: None,
+            passed: 0,
+            total,
+            results: Vec::new(),
+        };
+
+        ctx.report_progress(0.0, Some(steps), "コンパイル中");
+        let workspace = Workspace::create()?;
+        let program = match runner::build(language, &source, &workspace).await? {
+            Build::Ready(program) => program,
+            Build::CompileError(message) => {
+                report.compile_error = Some(truncate(&message));
+                return Ok(report);
+            }
+        };
+        let judge_workspace = Workspace::create()?;
+        let judge = build_helper(
+            "judge",
+            args.judge.as_ref(),
+            args.judge_path.as_ref(),
+            args.judge_language.as_ref().unwrap_or(&args.language),
+            ctx.config(),
+            &judge_workspace,
+        )
+        .await?;
+
+        for (i, case) in cases.into_iter().enumerate() {
+            let number = i as u32 + 1;
+            ctx.report_progress(
+                f64::from(number),
+                Some(steps),
+                format!("テストケース {} を実行中", number),
+            );
+            let mut judge_args = Vec::new();
+            if let Some(case) = case {
+                let path = judge_workspace.path().join(format!("case_{}.txt", number));
+                tokio::fs::write(&path, case)
+                    .await
+                    .with_context(|| format!("Failed to write {}", path.display()))?;
+                judge_args.push(path.to_string_lossy().into_owned());
+            }
+
+            let session = runner::interact(
+                &program,
+                &judge,
+                &judge_args,
+                &limits,
+                &judge_limits,
+                &query_limit,
+            )
+            .await?;
+            let judgement = runner::judge_session(&session, &limits, &query_limit);
+            if judgement.verdict == Verdict::Accepted {
+                report.passed += 1;
+            }
+            let stderr = session.solution.stderr.trim();
+            report.results.push(InteractiveResult {
+                case: number,
+                verdict: judgement.verdict.as_str().to_string(),
+                time_ms: session.solution.elapsed.as_millis() as u64,
+                memory_kb: session.solution.peak_rss_bytes.map(|b| b / 1024),
+                queries: session.queries,
+                message: judgement.message.map(|m| truncate(&m)),
+                stderr: (!stderr.is_empty()).then(|| truncate(stderr)),
+                transcript: session
+                    .transcript
+                    .into_iter()
+                    .map(|(side, line)| TranscriptLine {
+                        from: side.as_str().to_string(),
+                        line,
+                    })
+                    .collect(),
+                transcript_truncated: session.truncated,
+            });
+        }
+
+        Ok(report)
+    }
+}
+
+impl ToolOutput for InteractiveReport {
+    fn output_schema() -> Option<Value> {
+        Some(InteractiveReport::schema())
+    }
+
+    fn into_content(self) -> (String, Option<Value>) {
+        let mut lines = Vec::new();
+        if let Some(message) = &self.compile_error {
+            lines.push(format!(
+                "{} ({}): CE (コンパイルエラー)\n```\n{}\n```",
+                self.problem_id, self.language, message
+            ));
+        } else {
+            lines.push(format!(
+                "{} ({}): {}/{} AC (制限 {} ms)",
+                self.problem_id, self.language, self.passed, self.total, self.time_limit_ms
+            ));
+            for result in &self.results {
+                let memory = result
+                    .memory_kb
+                    .map(|kb| format!(", {} KB", kb))
+                    .unwrap_or_default();
+                lines.push(format!(
+                    "テストケース {}: {} ({} ms{}, クエリ {} 回)",
+                    result.case, result.verdict, result.time_ms, memory, result.queries
+                ));
+                if let Some(message) = &result.message {
+                    lines.push(format!("judge: {}", message));
+                }
+                if let Some(stderr) = &result.stderr {
+                    lines.push(format!("stderr:\n```\n{}\n```", stderr));
+                }
+                // > は解答から判定プログラムへ、< は判定プログラムから解答へ
+                let mut transcript: Vec<String> = result
+                    .transcript
+                    .iter()
+                    .map(|t| {
+                        let arrow = if t.from == "solution" { ">" } else { "<" };
+                        format!("{} {}", arrow, t.line)
+                    })
+                    .collect();
+                if result.transcript_truncated {
+                    transcript.push("... (truncated)".to_string());
+                }
+                if !transcript.is_empty() {
+                    lines.push(format!("```\n{}\n```", transcript.join("\n")));
+                }
+            }
+        }
+        let structured = serde_json::to_value(&self).ok();
+        (lines.join("\n"), structured)
+    }
+}
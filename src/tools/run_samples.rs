@@ -0,0 +1,371 @@
+use super::{ProblemArgs, Tool, ToolAnnotations, ToolContext, ToolOutput};
+use crate::atcoder::Lang;
+use crate::config::{Config, LanguageConfig};
+use crate::runner::{
+    self, Build, Checker, CompareMode, Comparison, Limits, Program, Verdict, Workspace,
+};
+use crate::schema::SchemaType;
+use anyhow::Context;
+use serde_json::Value;
+
+/// レポートに載せる stderr やコンパイルエラーの長さの上限
+const MAX_MESSAGE_LEN: usize = 2000;
+
+/// compare: float で、問題文からも引数からも誤差が分からなかったときの値
+const DEFAULT_TOLERANCE: f64 = 1e-6;
+
+/// チェッカー1回あたりの実行時間制限 (ミリ秒)
+pub(super) const CHECKER_TIME_LIMIT_MS: u64 = 10_000;
+
+tool_args! {
+    /// run_samples の引数
+    pub struct RunSamplesArgs {
+        #[flatten]
+        problem: ProblemArgs,
+        /// 言語 (設定の languages のキー。既定では cpp, c, rust, python, pypy)
+        language: String where { "minLength": 1 },
+        /// ソースコード本体 (source_path とどちらか一方)
+        source: Option<String>,
+        /// ソースファイルのパス (source とどちらか一方)
+        source_path: Option<String>,
+        /// 出力の比較方法。省略時は checker があれば checker、問題文に許容誤差があれば float、それ以外は exact
+        compare: Option<CompareMode>,
+        /// float で許す絶対誤差・相対誤差 (省略時は問題文から読む)
+        tolerance: Option<f64> where { "minimum": 0 },
+        /// チェッカーのソースコード。`checker 入力 出力 想定解` の形で呼ばれ、終了コード 0 なら AC
+        checker: Option<String>,
+        /// チェッカーのソースファイルのパス (checker とどちらか一方)
+        checker_path: Option<String>,
+        /// チェッカーの言語 (省略時は language と同じ)
+        checker_language: Option<String>,
+    }
+}
+
+tool_output! {
+    /// サンプル1つ分の結果
+    pub struct SampleResult {
+        /// サンプル番号
+        index: u32,
+        /// 判定 (AC / WA / RE / TLE / MLE / OLE)
+        verdict: String,
+        /// 実行時間 (ミリ秒)
+        time_ms: u64,
+        /// CPU 時間 (ミリ秒)
+        cpu_time_ms: u64,
+        /// 最大メモリ使用量 (KB, 計測できたとき)
+        memory_kb: Option<u64>,
+        /// 期待出力との差分かチェッカーのメッセージ (WA のとき)
+        diff: Option<String>,
+        /// 標準エラー出力 (空でなければ)
+        stderr: Option<String>,
+    }
+}
+
+tool_output! {
+    /// run_samples の結果
+    pub struct SampleRunReport {
+        /// 問題ID
+        problem_id: String,
+        /// 言語
+        language: String,
+        /// 実行時間制限 (ミリ秒)
+        time_limit_ms: u64,
+        /// メモリ制限 (MB)
+        memory_limit_mb: Option<u64>,
+        /// 出力の比較方法
+        compare: String,
+        /// コンパイルエラーの内容 (コンパイルに失敗したとき)
+        compile_error: Option<String>,
+        /// AC だったサンプルの数
+        passed: u32,
+        /// サンプルの総数
+        total: u32,
+        /// サンプルごとの結果
+        results: Vec<SampleResult>,
+    }
+}
+
+/// 公式サンプルで解答をテストするツール
+pub struct RunSamples;
+
+impl Tool for RunSamples {
+    type Args = RunSamplesArgs;
+    type Output = SampleRunReport;
+
+    const NAME: &'static str = "run_samples";
+    const DESCRIPTION: &'static str = "解答をコンパイルし、AtCoderの問題ページのサンプル入出力で実行して AC/WA/RE/TLE/MLE/OLE を判定します。実行時間・メモリは問題の制限に合わせて制限されます。source (コード本体) か source_path (ファイルのパス) のどちらかと、language (cpp, rust, python など) が必要です。誤差許容の問題は問題文から自動で float 比較になり、答えが複数ある問題は checker を渡せます。";
+    const ANNOTATIONS: ToolAnnotations = ToolAnnotations {
+        title: Some("サンプルでテスト"),
+        read_only: false,
+        destructive: false,
+        idempotent: true,
+        open_world: true,
+    };
+
+    async fn call(
+        &self,
+        args: RunSamplesArgs,
+        ctx: ToolContext,
+    ) -> anyhow::Result<SampleRunReport> {
+        let source = read_source(args.source.as_ref(), args.source_path.as_ref(), "source").await?;
+        let language = find_language(ctx.config(), &args.language)?;
+        let problem = args.problem.reference()?;
+
+        // サンプルはどの言語版でも同じなので日本語版から取る
+        let page = ctx
+            .atcoder()
+            .fetch_problem(&problem.contest_id, &problem.problem_id, Lang::Ja)
+            .await?;
+        let samples = page.problem.samples;
+        if samples.is_empty() {
+            anyhow::bail!(
+                "No samples found for {} (interactive problems have no samples)",
+                page.problem.problem_id
+            );
+        }
+        let limits = Limits::for_problem(
+            page.problem.time_limit_ms,
+            page.problem.memory_limit_mb,
+            &ctx.config().sandbox,
+        );
+
+        let total = samples.len() as u32;
+        let steps = f64::from(total + 1);
+        let mut report = SampleRunReport {
+            problem_id: page.problem.problem_id.clone(),
+            language: args.language.clone(),
+            compare: String::new(),
+            time_limit_ms: limits.time.as_millis() as u64,
+            memory_limit_mb: limits.memory_bytes.map(|b| b / 1024 / 1024),
+            compile_error: None,
+            passed: 0,
+            total,
+            results: Vec::new(),
+        };
+
+        ctx.report_progress(0.0, Some(steps), "コンパイル中");
+        let workspace = Workspace::create()?;
+        let program = match runner::build(language, &source, &workspace).await? {
+            Build::Ready(program) => program,
+            Build::CompileError(message) => {
+                report.compile_error = Some(truncate(&message));
+                return Ok(report);
+            }
+        };
+
+        let (checker, compare) = select_checker(
+            CompareOptions {
+                compare: args.compare,
+                tolerance: args.tolerance.or(page.problem.tolerance),
+                checker: args.checker.as_ref(),
+                checker_path: args.checker_path.as_ref(),
+                checker_language: args.checker_language.as_ref().unwrap_or(&args.language),
+            },
+            ctx.config(),
+        )
+        .await?;
+        report.compare = compare;
+
+        for (i, sample) in samples.iter().enumerate() {
+            ctx.report_progress(
+                (i + 1) as f64,
+                Some(steps),
+                format!("サンプル {} を実行中", sample.index),
+            );
+            let execution = program.run(&sample.input, &limits).await?;
+            let judgement =
+                runner::judge(&execution, &limits, &checker, &sample.input, &sample.output).await?;
+            let verdict = judgement.verdict;
+            if verdict == Verdict::Accepted {
+                report.passed += 1;
+            }
+            report.results.push(SampleResult {
+                index: sample.index,
+                verdict: verdict.as_str().to_string(),
+                time_ms: execution.elapsed.as_millis() as u64,
+                cpu_time_ms: execution.cpu_time.as_millis() as u64,
+                memory_kb: execution.peak_rss_bytes.map(|b| b / 1024),
+                diff: judgement.message.map(|m| truncate(&m)),
+                stderr: (!execution.stderr.trim().is_empty()).then(|| truncate(&execution.stderr)),
+            });
+        }
+
+        Ok(report)
+    }
+}
+
+/// `source` (本体) か `source_path` (ファイル) のどちらか一方からソースを読む
+pub(super) async fn read_source(
+    source: Option<&String>,
+    source_path: Option<&String>,
+    name: &str,
+) -> anyhow::Result<String> {
+    match (source, source_path) {
+        (Some(source), None) => Ok(source.clone()),
+        (None, Some(path)) => tokio::fs::read_to_string(path)
+            .await
+            .with_context(|| format!("Failed to read {}", path)),
+        _ => anyhow::bail!("Specify exactly one of {} or {}_path", name, name),
+    }
+}
+
+/// 設定から言語を探す (無ければ使える言語を並べて返す)
+pub(super) fn find_language<'a>(
+    config: &'a Config,
+    name: &str,
+) -> anyhow::Result<&'a LanguageConfig> {
+    config.languages.get(name).with_context(|| {
+        let known: Vec<&str> = config.languages.keys().map(String::as_str).collect();
+        format!(
+            "Unknown language: {} (available: {})",
+            name,
+            known.join(", ")
+        )
+    })
+}
+
+/// 出力の比較方法の指定 (run_samples と stress_test で共通)
+pub(super) struct CompareOptions<'a> {
+    pub compare: Option<CompareMode>,
+    /// 引数か問題文から分かった許容誤差
+    pub tolerance: Option<f64>,
+    pub checker: Option<&'a String>,
+    pub checker_path: Option<&'a String>,
+    pub checker_language: &'a str,
+}
+
+/// 比較方法を決める。省略時は checker があれば checker、許容誤差があれば float、それ以外は exact
+/// checker と組み込みの比較方法を同時に指定されたらエラーにする
+/// レポートに載せる比較方法の説明も返す
+pub(super) async fn select_checker(
+    options: CompareOptions<'_>,
+    config: &Config,
+) -> anyhow::Result<(Checker, String)> {
+    let has_checker = options.checker.is_some() || options.checker_path.is_some();
+    // checker を渡されたのに組み込みの比較を指定されたら、黙って捨てずにエラーにする
+    if has_checker
+        && matches!(
+            options.compare,
+            Some(CompareMode::Exact | CompareMode::Tokens | CompareMode::Float)
+        )
+    {
+        anyhow::bail!(
+            "checker / checker_path is only used when compare is \"checker\". Remove the checker or set compare to \"checker\""
+        );
+    }
+    let comparison = match options.compare {
+        Some(CompareMode::Checker) => None,
+        None if has_checker => None,
+        Some(CompareMode::Exact) => Some(Comparison::Exact),
+        Some(CompareMode::Tokens) => Some(Comparison::Tokens),
+        Some(CompareMode::Float) => Some(Comparison::Float(
+            options.tolerance.unwrap_or(DEFAULT_TOLERANCE),
+        )),
+        None => Some(
+            options
+                .tolerance
+                .map_or(Comparison::Exact, Comparison::Float),
+        ),
+    };
+    if let Some(comparison) = comparison {
+        return Ok((Checker::Compare(comparison), comparison.to_string()));
+    }
+
+    // 一時ディレクトリはチェッカーを使うときだけ作る
+    let workspace = Workspace::create()?;
+    let program = build_helper(
+        "checker",
+        options.checker,
+        options.checker_path,
+        options.checker_language,
+        config,
+        &workspace,
+    )
+    .await?;
+    let limits = Limits::for_problem(Some(CHECKER_TIME_LIMIT_MS), None, &config.sandbox);
+    Ok((
+        Checker::Program {
+            program,
+            limits,
+            workspace,
+        },
+        format!("checker ({})", options.checker_language),
+    ))
+}
+
+/// チェッカーなど補助のプログラムをビルドする。コンパイルエラーはツールのエラーにする
+/// `name` は引数名 (`{name}` と `{name}_path` のどちらか一方でソースを受け取る)
+pub(super) async fn build_helper(
+    name: &str,
+    source: Option<&String>,
+    source_path: Option<&String>,
+    language: &str,
+    config: &Config,
+    workspace: &Workspace,
+) -> anyhow::Result<Program> {
+    let source = read_source(source, source_path, name).await?;
+    let language = find_language(config, language)?;
+    match runner::build(language, &source, workspace).await? {
+        Build::Ready(program) => Ok(program),
+        Build::CompileError(message) => {
+            anyhow::bail!("{} failed to compile:\n{}", name, truncate(&message))
+        }
+    }
+}
+
+/// 長いメッセージは先頭だけ残す
+pub(super) fn truncate(text: &str) -> String {
+    match text.char_indices().nth(MAX_MESSAGE_LEN) {
+        Some((i, _)) => format!("{}\n... (truncated)", &text[..i]),
+        None => text.to_string(),
+    }
+}
+
+impl ToolOutput for SampleRunReport {
+    fn output_schema() -> Option<Value> {
+        Some(SampleRunReport::schema())
+    }
+
+    fn into_content(self) -> (String, Option<Value>) {
+        let mut lines = Vec::new();
+        if let Some(message) = &self.compile_error {
+            lines.push(format!(
+                "{} ({}): CE (コンパイルエラー)\n```\n{}\n```",
+                self.problem_id, self.language, message
+            ));
+        } else {
+            let memory_limit = self
+                .memory_limit_mb
+                .map(|mb| format!(", {} MB", mb))
+                .unwrap_or_default();
+            lines.push(format!(
+                "{} ({}): {}/{} AC (制限 {} ms{}, 比較 {})",
+                self.problem_id,
+                self.language,
+                self.passed,
+                self.total,
+                self.time_limit_ms,
+                memory_limit,
+                self.compare
+            ));
+            for result in &self.results {
+                let memory = result
+                    .memory_kb
+                    .map(|kb| format!(", {} KB", kb))
+                    .unwrap_or_default();
+                lines.push(format!(
+                    "サンプル {}: {} ({} ms{})",
+                    result.index, result.verdict, result.time_ms, memory
+                ));
+                if let Some(diff) = &result.diff {
+                    lines.push(format!("```diff\n{}\n```", diff));
+                }
+                if let Some(stderr) = &result.stderr {
+                    lines.push(format!("stderr:\n```\n{}\n```", stderr));
+                }
+            }
+        }
+        let structured = serde_json::to_value(&self).ok();
+        (lines.join("\n"), structured)
+    }
+}
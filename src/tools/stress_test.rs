@@ -0,0 +1,542 @@
+use super::run_samples::{
+    CHECKER_TIME_LIMIT_MS, CompareOptions, build_helper, find_language, read_source,
+    select_checker, truncate,
+};
+use super::{ProblemArgs, Tool, ToolAnnotations, ToolContext, ToolOutput};
+use crate::atcoder::Lang;
+use crate::runner::{
+    self, Build, Checker, CompareMode, Execution, Judgement, Limits, Program, Termination, Verdict,
+    Workspace,
+};
+use crate::schema::SchemaType;
+use serde_json::Value;
+use std::time::{Duration, Instant};
+
+/// 試行回数の既定値
+const DEFAULT_ITERATIONS: u32 = 500;
+
+/// 時間の予算の既定値 (ミリ秒)
+const DEFAULT_TIME_BUDGET_MS: u64 = 60_000;
+
+/// 最初の反例が見つかったあと、より小さい反例を探して続ける回数の既定値
+const DEFAULT_KEEP_SEARCHING: u32 = 100;
+
+/// 進捗を通知する間隔 (試行回数)
+const PROGRESS_INTERVAL: u32 = 10;
+
+tool_args! {
+    /// stress_test の引数
+    pub struct StressTestArgs {
+        #[flatten]
+        problem: ProblemArgs,
+        /// 解答の言語 (設定の languages のキー)
+        language: String where { "minLength": 1 },
+        /// 解答のソースコード (source_path とどちらか一方)
+        source: Option<String>,
+        /// 解答のソースファイルのパス (source とどちらか一方)
+        source_path: Option<String>,
+        /// 愚直解のソースコード (brute_path とどちらか一方)。この出力を正解として比べる
+        brute: Option<String>,
+        /// 愚直解のソースファイルのパス
+        brute_path: Option<String>,
+        /// 愚直解の言語 (省略時は language と同じ)
+        brute_language: Option<String>,
+        /// 入力生成プログラムのソースコード (generator_path とどちらか一方)。第1引数でシード値を受け取り、入力を標準出力に書く
+        generator: Option<String>,
+        /// 入力生成プログラムのソースファイルのパス
+        generator_path: Option<String>,
+        /// 入力生成プログラムの言語 (省略時は language と同じ)
+        generator_language: Option<String>,
+        /// 試行回数の上限
+        max_iterations: Option<u32> where { "minimum": 1, "maximum": 100000 },
+        /// 時間の予算 (ミリ秒)
+        time_budget_ms: Option<u64> where { "minimum": 1000, "maximum": 600000 },
+        /// 最初の反例が見つかったあと、入力のより短い反例を探して続ける回数 (0 ならすぐ止める)
+        keep_searching: Option<u32>,
+        /// 最初のシード値 (試行ごとに1ずつ増やす。既定は 1)
+        seed: Option<u64>,
+        /// 出力の比較方法。省略時は checker があれば checker、許容誤差があれば float、それ以外は exact
+        compare: Option<CompareMode>,
+        /// float で許す絶対誤差・相対誤差
+        tolerance: Option<f64> where { "minimum": 0 },
+        /// チェッカーのソースコード。`checker 入力 出力 想定解` の形で呼ばれ、終了コード 0 なら AC
+        checker: Option<String>,
+        /// チェッカーのソースファイルのパス
+        checker_path: Option<String>,
+        /// チェッカーの言語 (省略時は language と同じ)
+        checker_language: Option<String>,
+    }
+}
+
+tool_output! {
+    /// 見つかった反例
+    pub struct Counterexample {
+        /// 入力を生成したシード値
+        seed: u64,
+        /// 解答の判定 (WA / RE / TLE / MLE / OLE)
+        verdict: String,
+        /// 入力 (長いときは先頭だけ。seed と generator で再現できる)
+        input: String,
+        /// 愚直解の出力
+        expected: String,
+        /// 解答の出力
+        output: String,
+        /// 差分かチェッカーのメッセージ
+        message: Option<String>,
+        /// 解答の標準エラー出力 (空でなければ)
+        stderr: Option<String>,
+    }
+}
+
+tool_output! {
+    /// stress_test の結果
+    pub struct StressReport {
+        /// 言語
+        language: String,
+        /// 出力の比較方法
+        compare: String,
+        /// 解答のコンパイルエラー (コンパイルに失敗したとき)
+        compile_error: Option<String>,
+        /// 実行した回数
+        iterations: u32,
+        /// 解答が愚直解と合わなかった回数
+        failures: u32,
+        /// かかった時間 (ミリ秒)
+        elapsed_ms: u64,
+        /// 見つかった反例のうち入力が最も短いもの (入力を縮めて探し直すことはしない)
+        shortest_counterexample: Option<Counterexample>,
+    }
+}
+
+/// ランダムな入力で解答と愚直解を突き合わせるツール
+pub struct StressTest;
+
+impl Tool for StressTest {
+    type Args = StressTestArgs;
+    type Output = StressReport;
+
+    const NAME: &'static str = "stress_test";
+    const DESCRIPTION: &'static str = "入力生成プログラム (generator)・愚直解 (brute)・解答 (source) を受け取り、ランダムな入力で解答と愚直解の出力が食い違うまで繰り返し実行します。見つかった反例のうち入力が最も短いものを返します (入力を縮める処理はしないので、最小の反例とは限りません)。サンプルは通るのに WA になるときのデバッグに使います。generator は第1引数でシード値を受け取ります。問題 (problem か contest_id と problem_id) は任意で、指定するとその問題の実行時間・メモリの制限と許容誤差を使います。";
+    const ANNOTATIONS: ToolAnnotations = ToolAnnotations {
+        title: Some("ストレステスト"),
+        read_only: false,
+        destructive: false,
+        idempotent: true,
+        open_world: false,
+    };
+
+    async fn call(&self, args: StressTestArgs, ctx: ToolContext) -> anyhow::Result<StressReport> {
+        let source = read_source(args.source.as_ref(), args.source_path.as_ref(), "source").await?;
+        let language = find_language(ctx.config(), &args.language)?;
+        let config = ctx.config();
+
+        // 問題が分かれば、その制限と許容誤差を使う
+        let reference = args.problem.optional_reference()?;
+        let problem = match reference {
+            Some(problem) => Some(
+                ctx.atcoder()
+                    .fetch_problem(&problem.contest_id, &problem.problem_id, Lang::Ja)
+                    .await?
+                    .problem,
+            ),
+            None => None,
+        };
+        let limits = Limits::for_problem(
+            problem.as_ref().and_then(|p| p.time_limit_ms),
+            problem.as_ref().and_then(|p| p.memory_limit_mb),
+            &config.sandbox,
+        );
+        // 愚直解と入力生成は遅くてもよいので、チェッカーと同じ緩い制限にする
+        let helper_limits = Limits::for_problem(Some(CHECKER_TIME_LIMIT_MS), None, &config.sandbox);
+
+        let mut report = StressReport {
+            language: args.language.clone(),
+            compare: String::new(),
+            compile_error: None,
+            iterations: 0,
+            failures: 0,
+            elapsed_ms: 0,
+            shortest_counterexample: None,
+        };
+
+        ctx.report_progress(0.0, None, "コンパイル中");
+        let workspace = Workspace::create()?;
+        let program = match runner::build(language, &source, &workspace).await? {
+            Build::Ready(program) => program,
+            Build::CompileError(message) => {
+                report.compile_error = Some(truncate(&message));
+                return Ok(report);
+            }
+        };
+        let brute_workspace = Workspace::create()?;
+        let brute = build_helper(
+            "brute",
+            args.brute.as_ref(),
+            args.brute_path.as_ref(),
+            args.brute_language.as_ref().unwrap_or(&args.language),
+            config,
+            &brute_workspace,
+        )
+        .await?;
+        let generator_workspace = Workspace::create()?;
+        let generator = build_helper(
+            "generator",
+            args.generator.as_ref(),
+            args.generator_path.as_ref(),
+            args.generator_language.as_ref().unwrap_or(&args.language),
+            config,
+            &generator_workspace,
+        )
+        .await?;
+        let (checker, compare) = select_checker(
+            CompareOptions {
+                compare: args.compare,
+                tolerance: args
+                    .tolerance
+                    .or(problem.as_ref().and_then(|p| p.tolerance)),
+                checker: args.checker.as_ref(),
+                checker_path: args.checker_path.as_ref(),
+                checker_language: args.checker_language.as_ref().unwrap_or(&args.language),
+            },
+            config,
+        )
+        .await?;
+        report.compare = compare;
+
+        let max_iterations = args.max_iterations.unwrap_or(DEFAULT_ITERATIONS);
+        let budget = Duration::from_millis(args.time_budget_ms.unwrap_or(DEFAULT_TIME_BUDGET_MS));
+        let keep_searching = args.keep_searching.unwrap_or(DEFAULT_KEEP_SEARCHING);
+        let first_seed = args.seed.unwrap_or(1);
+        let start = Instant::now();
+        let deadline = start + budget;
+        let harness = Harness {
+            program: &program,
+            brute: &brute,
+            generator: &generator,
+            checker: &checker,
+            limits: &limits,
+            helper_limits: &helper_limits,
+        };
+        // 最初の反例が見つかってから試した回数
+        let mut since_failure = None;
+        let mut shortest_input = usize::MAX;
+
+        while report.iterations < max_iterations && since_failure.is_none_or(|n| n < keep_searching)
+        {
+            let seed = first_seed.wrapping_add(u64::from(report.iterations));
+            // 予算が尽きたら、途中のシードは数えずに終える
+            let Some(trial) = harness.trial(seed, deadline).await? else {
+                break;
+            };
+            report.iterations += 1;
+            if let Some(n) = since_failure.as_mut() {
+                *n += 1;
+            }
+            if report.iterations.is_multiple_of(PROGRESS_INTERVAL) {
+                ctx.report_progress(
+                    f64::from(report.iterations),
+                    Some(f64::from(max_iterations)),
+                    format!("{} 回実行 (反例 {} 件)", report.iterations, report.failures),
+                );
+            }
+            if trial.judgement.verdict == Verdict::Accepted {
+                continue;
+            }
+
+            report.failures += 1;
+            since_failure.get_or_insert(0);
+            // 入力の短いものほど追いかけやすいので、短い反例だけ残す
+            if trial.input.len() < shortest_input {
+                shortest_input = trial.input.len();
+                report.shortest_counterexample = Some(trial.into_counterexample(seed));
+            }
+        }
+
+        report.elapsed_ms = start.elapsed().as_millis() as u64;
+        Ok(report)
+    }
+}
+
+/// 1つのシードで突き合わせるのに使うもの
+struct Harness<'a> {
+    program: &'a Program,
+    brute: &'a Program,
+    generator: &'a Program,
+    checker: &'a Checker,
+    limits: &'a Limits,
+    helper_limits: &'a Limits,
+}
+
+/// 1つのシードで突き合わせた結果
+struct Trial {
+    input: String,
+    expected: Execution,
+    execution: Execution,
+    judgement: Judgement,
+}
+
+impl Harness<'_> {
+    /// 入力を作り、愚直解と解答を動かして比べる
+    /// どの実行も予算の残りを実時間の上限にし、それに掛かって止まったら None
+    async fn trial(&self, seed: u64, deadline: Instant) -> anyhow::Result<Option<Trial>> {
+        let Some(helper_limits) = within_budget(self.helper_limits, deadline) else {
+            return Ok(None);
+        };
+        let generated = self
+            .generator
+            .run_with_args(&[seed.to_string()], "", &helper_limits)
+            .await?;
+        if stopped_by_budget(&generated, &helper_limits, self.helper_limits) {
+            return Ok(None);
+        }
+        if !generated.success() {
+            anyhow::bail!(
+                "Generator failed with seed {} ({:?}):\n{}",
+                seed,
+                generated.termination,
+                truncate(&generated.stderr)
+            );
+        }
+        let input = generated.stdout;
+
+        let Some(helper_limits) = within_budget(self.helper_limits, deadline) else {
+            return Ok(None);
+        };
+        let expected = self.brute.run(&input, &helper_limits).await?;
+        if stopped_by_budget(&expected, &helper_limits, self.helper_limits) {
+            return Ok(None);
+        }
+        if let Some(verdict) = runner::limit_verdict(&expected, self.helper_limits) {
+            anyhow::bail!(
+                "Brute-force solution got {} with seed {}:\ninput:\n{}\nstderr:\n{}",
+                verdict.as_str(),
+                seed,
+                truncate(&input),
+                truncate(&expected.stderr)
+            );
+        }
+
+        let Some(limits) = within_budget(self.limits, deadline) else {
+            return Ok(None);
+        };
+        let execution = self.program.run(&input, &limits).await?;
+        if stopped_by_budget(&execution, &limits, self.limits) {
+            return Ok(None);
+        }
+        let judgement = runner::judge(
+            &execution,
+            self.limits,
+            self.checker,
+            &input,
+            &expected.stdout,
+        )
+        .await?;
+        Ok(Some(Trial {
+            input,
+            expected,
+            execution,
+            judgement,
+        }))
+    }
+}
+
+impl Trial {
+    fn into_counterexample(self, seed: u64) -> Counterexample {
+        let stderr = self.execution.stderr.trim();
+        Counterexample {
+            seed,
+            verdict: self.judgement.verdict.as_str().to_string(),
+            input: truncate(&self.input),
+            expected: truncate(&self.expected.stdout),
+            output: truncate(&self.execution.stdout),
+            message: self.judgement.message.map(|m| truncate(&m)),
+            stderr: (!stderr.is_empty()).then(|| truncate(stderr)),
+        }
+    }
+}
+
+/// 予算の残りを実時間の上限にした制限 (予算が尽きていたら None)
+fn within_budget(limits: &Limits, deadline: Instant) -> Option<Limits> {
+    let remaining = deadline.saturating_duration_since(Instant::now());
+    if remaining.is_zero() {
+        return None;
+    }
+    Some(Limits {
+        wall_time: limits.wall_time.min(remaining),
+        ..limits.clone()
+    })
+}
+
+/// 予算で縮めた実時間の上限に掛かって止まったか (プログラムのせいとは限らないので判定しない)
+fn stopped_by_budget(execution: &Execution, budgeted: &Limits, limits: &Limits) -> bool {
+    budgeted.wall_time < limits.wall_time
+        && execution.termination == Termination::TimeLimit
+        && execution.elapsed >= budgeted.wall_time
+}
+
+impl ToolOutput for StressReport {
+    fn output_schema() -> Option<Value> {
+        Some(StressReport::schema())
+    }
+
+    fn into_content(self) -> (String, Option<Value>) {
+        let mut lines = Vec::new();
+        if let Some(message) = &self.compile_error {
+            lines.push(format!(
+                "{}: CE (コンパイルエラー)\n```\n{}\n```",
+                self.language, message
+            ));
+        } else {
+            lines.push(format!(
+                "{} 回実行 ({} ms, 比較 {}): 反例 {} 件",
+                self.iterations, self.elapsed_ms, self.compare, self.failures
+            ));
+            match &self.shortest_counterexample {
+                Some(c) => {
+                    lines.push(format!(
+                        "入力が最も短い反例 (seed {}): {}",
+                        c.seed, c.verdict
+                    ));
+                    lines.push(format!("入力:\n```\n{}\n```", c.input.trim_end()));
+                    lines.push(format!(
+                        "愚直解の出力:\n```\n{}\n```",
+                        c.expected.trim_end()
+                    ));
+                    lines.push(format!("解答の出力:\n```\n{}\n```", c.output.trim_end()));
+                    if let Some(message) = &c.message {
+                        lines.push(format!("```diff\n{}\n```", message));
+                    }
+                    if let Some(stderr) = &c.stderr {
+                        lines.push(format!("stderr:\n```\n{}\n```", stderr));
+                    }
+                }
+                None => lines.push("反例は見つかりませんでした。".to_string()),
+            }
+        }
+        let structured = serde_json::to_value(&self).ok();
+        (lines.join("\n"), structured)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::atcoder::AtCoder;
+    use crate::config::{Config, LanguageConfig};
+    use crate::protocol::ProtocolVersion;
+    use crate::rpc::Outgoing;
+    use crate::schema::parse_args;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    /// 長さがシードで変わる入力 (シード 1..=5 で 3, 5, 2, 4, 1 文字)
+    const GENERATOR: &str = "n=$(( 7 * $1 % 5 + 1 )); printf \"%${n}s\\n\" | tr ' ' a";
+
+    fn context() -> ToolContext {
+        let mut config = Config::default();
+        config.languages.insert(
+            "sh".to_string(),
+            LanguageConfig {
+                source_file: "main.sh".to_string(),
+                compile: None,
+                run: vec!["sh".to_string(), "{src}".to_string()],
+                limit_address_space: true,
+            },
+        );
+        let atcoder = AtCoder::new(&config).unwrap();
+        let (outgoing, _) = Outgoing::channel();
+        ToolContext::from_params(
+            &json!({}),
+            &outgoing,
+            &Arc::new(config),
+            &Arc::new(atcoder),
+            ProtocolVersion::LATEST,
+        )
+    }
+
+    async fn stress(source: &str, extra: Value) -> StressReport {
+        let mut args = json!({
+            "language": "sh",
+            "source": source,
+            "brute": "cat",
+            "generator": GENERATOR,
+        });
+        args.as_object_mut()
+            .unwrap()
+            .extend(extra.as_object().unwrap().clone());
+        let args: StressTestArgs = parse_args(args).unwrap();
+        StressTest.call(args, context()).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn passes_when_outputs_match() {
+        let report = stress("cat", json!({ "max_iterations": 3 })).await;
+        assert_eq!((report.iterations, report.failures), (3, 0));
+        assert!(report.shortest_counterexample.is_none());
+        assert_eq!(report.compare, "exact");
+    }
+
+    #[tokio::test]
+    async fn keeps_shortest_counterexample() {
+        let report = stress(
+            "read x; echo \"${x}b\"",
+            json!({ "max_iterations": 5, "keep_searching": 10 }),
+        )
+        .await;
+        assert_eq!((report.iterations, report.failures), (5, 5));
+        let counterexample = report.shortest_counterexample.unwrap();
+        assert_eq!(counterexample.seed, 5);
+        assert_eq!(counterexample.verdict, "WA");
+        assert_eq!(counterexample.input, "a\n");
+        assert_eq!(counterexample.expected, "a\n");
+        assert_eq!(counterexample.output, "ab\n");
+    }
+
+    #[tokio::test]
+    async fn stops_keep_searching_after_first_failure() {
+        let report = stress(
+            "read x; echo \"${x}b\"",
+            json!({ "max_iterations": 5, "keep_searching": 1, "seed": 2 }),
+        )
+        .await;
+        assert_eq!((report.iterations, report.failures), (2, 2));
+        // シード 2, 3 のうち入力の短い方
+        assert_eq!(report.shortest_counterexample.unwrap().seed, 3);
+
+        let report = stress(
+            "exit 1",
+            json!({ "max_iterations": 5, "keep_searching": 0 }),
+        )
+        .await;
+        assert_eq!((report.iterations, report.failures), (1, 1));
+        assert_eq!(report.shortest_counterexample.unwrap().verdict, "RE");
+    }
+
+    #[tokio::test]
+    async fn time_budget_bounds_each_run() {
+        // 実時間の上限 (5 秒) より予算の残りが短いので、予算で打ち切る
+        let report = stress("sleep 10", json!({ "time_budget_ms": 1000 })).await;
+        assert_eq!((report.iterations, report.failures), (0, 0));
+        assert!(report.shortest_counterexample.is_none());
+        assert!(report.elapsed_ms < 3000, "{}", report.elapsed_ms);
+    }
+
+    #[test]
+    fn budget_shortens_wall_time() {
+        let limits = runner::Limits {
+            time: Duration::from_secs(2),
+            wall_time: Duration::from_secs(5),
+            memory_bytes: None,
+            limit_address_space: false,
+            output_bytes: 1024,
+            extra_processes: None,
+        };
+        let now = Instant::now();
+        assert!(within_budget(&limits, now).is_none());
+        let far = within_budget(&limits, now + Duration::from_secs(60)).unwrap();
+        assert_eq!(far.wall_time, limits.wall_time);
+        let near = within_budget(&limits, now + Duration::from_millis(500)).unwrap();
+        assert!(near.wall_time <= Duration::from_millis(500));
+        assert_eq!(near.time, limits.time);
+    }
+}